@@ -0,0 +1,96 @@
+// Steps a space exported with export_json or export_binary outside of Godot, eg. for profiling.
+// Usage: godot-rapier-replay <export file> [--steps N] [--dt DT] [--gravity X,Y(,Z)] ...
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use godot_rapier::replay::ReplayOptions;
+const USAGE: &str = "Usage: godot-rapier-replay <export file> [options]
+Files ending in .json are read as json exports, everything else as binary exports.
+Options not given use the settings stored in the export.
+Options:
+  --steps N                               number of steps (default 60)
+  --dt DT                                 step length in seconds (default 1/60)
+  --length-unit VALUE                     solver length unit
+  --solver-iterations N                   solver iterations
+  --additional-friction-iterations N      additional friction iterations
+  --internal-pgs-iterations N             internal pgs iterations
+  --max-ccd-substeps N                    max ccd substeps
+  --gravity X,Y(,Z)                       gravity
+  --liquid-gravity X,Y(,Z)                fluid gravity (default gravity)
+  --fluid-particle-radius VALUE           fluid particle radius
+  --fluid-smoothing-factor VALUE          fluid smoothing factor";
+fn parse_value<T: std::str::FromStr>(name: &str, value: Option<String>) -> Result<T, String> {
+    let Some(value) = value else {
+        return Err(format!("Missing value for {}", name));
+    };
+    value
+        .parse::<T>()
+        .map_err(|_| format!("Invalid value for {}: {}", name, value))
+}
+fn parse_vector(name: &str, value: Option<String>) -> Result<Vec<f64>, String> {
+    let Some(value) = value else {
+        return Err(format!("Missing value for {}", name));
+    };
+    value
+        .split(',')
+        .map(|component| parse_value(name, Some(component.trim().to_string())))
+        .collect()
+}
+fn parse_args() -> Result<(PathBuf, ReplayOptions), String> {
+    let mut options = ReplayOptions::default();
+    let mut path = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--steps" => options.steps = parse_value(&arg, args.next())?,
+            "--dt" => options.dt = parse_value(&arg, args.next())?,
+            "--length-unit" => options.length_unit = Some(parse_value(&arg, args.next())?),
+            "--solver-iterations" => {
+                options.num_solver_iterations = Some(parse_value(&arg, args.next())?)
+            }
+            "--additional-friction-iterations" => {
+                options.num_additional_friction_iterations = Some(parse_value(&arg, args.next())?)
+            }
+            "--internal-pgs-iterations" => {
+                options.num_internal_pgs_iterations = Some(parse_value(&arg, args.next())?)
+            }
+            "--max-ccd-substeps" => {
+                options.max_ccd_substeps = Some(parse_value(&arg, args.next())?)
+            }
+            "--gravity" => options.gravity = parse_vector(&arg, args.next())?,
+            "--liquid-gravity" => options.liquid_gravity = parse_vector(&arg, args.next())?,
+            "--fluid-particle-radius" => {
+                options.fluid_particle_radius = Some(parse_value(&arg, args.next())?)
+            }
+            "--fluid-smoothing-factor" => {
+                options.fluid_smoothing_factor = Some(parse_value(&arg, args.next())?)
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ if path.is_none() => path = Some(PathBuf::from(arg)),
+            _ => return Err(format!("Unexpected argument {}", arg)),
+        }
+    }
+    let Some(path) = path else {
+        return Err("Missing export file".to_string());
+    };
+    Ok((path, options))
+}
+fn main() -> ExitCode {
+    let (path, options) = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    match godot_rapier::replay::run(&path, &options) {
+        Ok(report) => {
+            println!("{}", report);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -36,14 +36,25 @@ pub enum AreaUpdateMode {
     ResetSpaceOverride,
     None,
 }
-// TODO deserialize
-#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct RapierArea {
-    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(with = "crate::types::serde_engine_enum")
+    )]
     gravity_override_mode: AreaSpaceOverrideMode,
-    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(with = "crate::types::serde_engine_enum")
+    )]
     linear_damping_override_mode: AreaSpaceOverrideMode,
-    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(with = "crate::types::serde_engine_enum")
+    )]
     angular_damping_override_mode: AreaSpaceOverrideMode,
     gravity: real,
     gravity_vector: Vector,
@@ -53,11 +64,25 @@ pub struct RapierArea {
     angular_damp: real,
     priority: i32,
     monitorable: bool,
-    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip, default = "Callable::invalid")
+    )]
     monitor_callback: Callable,
-    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip, default = "Callable::invalid")
+    )]
     area_monitor_callback: Callable,
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(with = "crate::types::serde_map_as_entries")
+    )]
     monitored_objects: HashMap<(ColliderHandle, ColliderHandle), MonitorInfo>,
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(with = "crate::types::serde_map_as_entries")
+    )]
     detected_bodies: HashMap<Rid, u32>,
     base: RapierCollisionObject,
 }
@@ -660,10 +685,23 @@ impl RapierArea {
             }
         }
     }
+
+    #[cfg(feature = "serde-serialize")]
+    pub fn load_state(&mut self, mut state: RapierArea) {
+        // Callbacks come from Godot, keep the current ones
+        std::mem::swap(&mut state.monitor_callback, &mut self.monitor_callback);
+        std::mem::swap(
+            &mut state.area_monitor_callback,
+            &mut self.area_monitor_callback,
+        );
+        std::mem::swap(self, &mut state);
+        // The handles are owned by the loaded state now
+        state.base.reset_handles();
+    }
 }
 // We won't use the pointers between threads, so it should be safe.
 unsafe impl Sync for RapierArea {}
-//#[cfg_attr(feature = "serde-serialize", typetag::serde)]
+#[cfg_attr(feature = "serde-serialize", typetag::serde)]
 impl IRapierCollisionObject for RapierArea {
     fn get_base(&self) -> &RapierCollisionObject {
         &self.base
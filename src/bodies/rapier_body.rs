@@ -4,7 +4,11 @@ use godot::classes::physics_server_2d::*;
 use godot::classes::physics_server_3d::*;
 use godot::prelude::*;
 use hashbrown::hash_set::HashSet;
+use rapier::dynamics::CoefficientCombineRule;
+use rapier::dynamics::LockedAxes;
 use rapier::geometry::ColliderHandle;
+use rapier::geometry::Group;
+use rapier::geometry::InteractionGroups;
 use rapier::math::Real;
 use servers::rapier_physics_server_extra::PhysicsCollisionObjects;
 use servers::rapier_physics_server_extra::PhysicsShapes;
@@ -73,7 +77,10 @@ pub struct ForceIntegrationCallbackData {
     pub udata: Variant,
 }
 #[derive(Clone, Copy)]
-#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct RidWithPriority {
     pub rid: Rid,
     pub priority: i32,
@@ -91,12 +98,20 @@ impl Default for RidWithPriority {
         }
     }
 }
-#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct RapierBody {
-    // TODO
-    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(with = "crate::types::serde_engine_enum")
+    )]
     linear_damping_mode: BodyDampMode,
-    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(with = "crate::types::serde_engine_enum")
+    )]
     angular_damping_mode: BodyDampMode,
     linear_damping: real,
     angular_damping: real,
@@ -106,12 +121,22 @@ pub struct RapierBody {
     gravity_scale: real,
     bounce: real,
     friction: real,
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    friction_combine_rule: Option<CoefficientCombineRule>,
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    restitution_combine_rule: Option<CoefficientCombineRule>,
     mass: real,
     mass_properties_update_pending: bool,
     inertia: Angle,
     #[cfg(feature = "dim3")]
     inv_inertia_tensor: Basis,
     contact_skin: real,
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    dominance: Option<i8>,
+    #[cfg_attr(feature = "serde-serialize", serde(default = "default_solver_groups"))]
+    solver_membership: u32,
+    #[cfg_attr(feature = "serde-serialize", serde(default = "default_solver_groups"))]
+    solver_filter: u32,
     center_of_mass: Vector,
     calculate_inertia: bool,
     calculate_center_of_mass: bool,
@@ -120,6 +145,9 @@ pub struct RapierBody {
     using_area_angular_damping: bool,
     exceptions: HashSet<Rid>,
     ccd_enabled: bool,
+    #[cfg(feature = "dim3")]
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    locked_axes: u8,
     omit_force_integration: bool,
     active: bool,
     marked_active: bool,
@@ -136,7 +164,10 @@ pub struct RapierBody {
     areas: Vec<RidWithPriority>,
     contacts: Vec<Contact>,
     contact_count: i32,
-    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip, default = "Callable::invalid")
+    )]
     body_state_callback: Callable,
     #[cfg_attr(feature = "serde-serialize", serde(skip))]
     fi_callback_data: Option<ForceIntegrationCallbackData>,
@@ -144,6 +175,10 @@ pub struct RapierBody {
     direct_state: Option<Gd<PhysicsDirectBodyState>>,
     base: RapierCollisionObject,
 }
+// Bodies are in the first solver group and only solve contacts with it by default
+fn default_solver_groups() -> u32 {
+    1
+}
 impl RapierBody {
     pub fn new(rid: Rid) -> Self {
         Self {
@@ -157,12 +192,17 @@ impl RapierBody {
             gravity_scale: 1.0,
             bounce: 0.0,
             friction: 1.0,
+            friction_combine_rule: None,
+            restitution_combine_rule: None,
             mass: 1.0,
             mass_properties_update_pending: false,
             inertia: ANGLE_ZERO,
             #[cfg(feature = "dim3")]
             inv_inertia_tensor: Basis::IDENTITY,
             contact_skin: RapierProjectSettings::get_contact_skin(),
+            dominance: None,
+            solver_membership: default_solver_groups(),
+            solver_filter: default_solver_groups(),
             center_of_mass: Vector::default(),
             calculate_inertia: true,
             calculate_center_of_mass: true,
@@ -171,6 +211,8 @@ impl RapierBody {
             using_area_angular_damping: false,
             exceptions: HashSet::default(),
             ccd_enabled: false,
+            #[cfg(feature = "dim3")]
+            locked_axes: 0,
             omit_force_integration: false,
             active: true,
             marked_active: false,
@@ -220,6 +262,8 @@ impl RapierBody {
         if self.base.mode == BodyMode::RIGID_LINEAR {
             inertia_value = ANGLE_ZERO;
         }
+        // Keep the locks in sync with the mode the mass properties were computed for
+        self.apply_locked_axes(false, physics_engine);
         // Force update means local properties will be re-calculated internally,
         // it's needed for applying forces right away (otherwise it's updated on next step)
         physics_engine.body_set_mass_properties(
@@ -233,6 +277,59 @@ impl RapierBody {
         );
     }
 
+    // The dominance param overrides the dominance from the collision priority when set
+    pub fn get_dominance_group(&self) -> i8 {
+        self.dominance.unwrap_or_else(|| {
+            self.base
+                .get_collision_priority_dominance()
+                .clamp(-127, 127) as i8
+        })
+    }
+
+    pub fn get_solver_groups(&self) -> InteractionGroups {
+        InteractionGroups::new(
+            Group::from(self.solver_membership),
+            Group::from(self.solver_filter),
+        )
+    }
+
+    // Bodies only push each other when each one's membership matches the other's filter
+    pub fn solver_groups_interact(&self, other: &RapierBody) -> bool {
+        self.get_solver_groups().test(other.get_solver_groups())
+    }
+
+    pub fn apply_dominance_group(&self, physics_engine: &mut PhysicsEngine) {
+        if !self.base.is_valid() {
+            return;
+        }
+        physics_engine.body_set_dominance_group(
+            self.base.get_space_handle(),
+            self.base.get_body_handle(),
+            self.get_dominance_group(),
+        );
+    }
+
+    fn apply_locked_axes(&self, wake_up: bool, physics_engine: &mut PhysicsEngine) {
+        if !self.base.is_valid() {
+            return;
+        }
+        let mut locked_axes = LockedAxes::empty();
+        // Godot's body axis flags use the same bits as rapier's locked axes
+        #[cfg(feature = "dim3")]
+        if self.base.mode.ord() >= BodyMode::RIGID.ord() {
+            locked_axes = LockedAxes::from_bits_truncate(self.locked_axes);
+        }
+        if self.base.mode == BodyMode::RIGID_LINEAR {
+            locked_axes |= LockedAxes::ROTATION_LOCKED;
+        }
+        physics_engine.body_set_locked_axes(
+            self.base.get_space_handle(),
+            self.base.get_body_handle(),
+            locked_axes,
+            wake_up,
+        );
+    }
+
     fn apply_linear_damping(
         &mut self,
         new_value: real,
@@ -291,7 +388,7 @@ impl RapierBody {
         );
     }
 
-    fn init_collider(
+    pub(super) fn init_collider(
         &self,
         collider_handle: ColliderHandle,
         space_handle: WorldHandle,
@@ -311,6 +408,20 @@ impl RapierBody {
         }
     }
 
+    pub fn merge_rectangle_shapes(
+        &mut self,
+        physics_engine: &mut PhysicsEngine,
+        physics_shapes: &mut PhysicsShapes,
+        physics_spaces: &mut PhysicsSpaces,
+    ) -> i32 {
+        RapierCollisionObject::merge_rectangle_shapes(
+            self,
+            physics_engine,
+            physics_shapes,
+            physics_spaces,
+        )
+    }
+
     pub fn to_add_static_constant_linear_velocity(&mut self, linear_velocity: Vector) {
         self.to_add_linear_velocity = linear_velocity;
     }
@@ -1289,15 +1400,93 @@ impl RapierBody {
                     physics_engine.body_update_material(space_handle, body_handle, &mat);
                 }
             }
+            RapierBodyParam::FrictionCombineRule => {
+                if p_value.get_type() != VariantType::INT {
+                    return;
+                }
+                self.friction_combine_rule = combine_rule_from_i32(p_value.to());
+                if !self.base.is_valid() {
+                    return;
+                }
+                physics_engine.body_update_material(
+                    self.base.get_space_handle(),
+                    self.base.get_body_handle(),
+                    &self.init_material(),
+                );
+            }
+            RapierBodyParam::RestitutionCombineRule => {
+                if p_value.get_type() != VariantType::INT {
+                    return;
+                }
+                self.restitution_combine_rule = combine_rule_from_i32(p_value.to());
+                if !self.base.is_valid() {
+                    return;
+                }
+                physics_engine.body_update_material(
+                    self.base.get_space_handle(),
+                    self.base.get_body_handle(),
+                    &self.init_material(),
+                );
+            }
+            RapierBodyParam::SolverMembership | RapierBodyParam::SolverFilter => {
+                if p_value.get_type() != VariantType::INT {
+                    return;
+                }
+                let groups = p_value.to::<i64>() as u32;
+                if matches!(p_param, RapierBodyParam::SolverMembership) {
+                    self.solver_membership = groups;
+                } else {
+                    self.solver_filter = groups;
+                }
+                if !self.base.is_valid() {
+                    return;
+                }
+                physics_engine.body_update_material(
+                    self.base.get_space_handle(),
+                    self.base.get_body_handle(),
+                    &self.init_material(),
+                );
+            }
+            RapierBodyParam::Dominance => {
+                match p_value.get_type() {
+                    VariantType::INT => {
+                        self.dominance = Some(p_value.to::<i64>().clamp(-127, 127) as i8);
+                    }
+                    VariantType::NIL => {
+                        self.dominance = None;
+                    }
+                    _ => {
+                        return;
+                    }
+                }
+                self.apply_dominance_group(physics_engine);
+            }
         }
     }
 
     pub fn get_extra_param(&self, p_param: RapierBodyParam) -> Variant {
         match p_param {
             RapierBodyParam::ContactSkin => self.contact_skin.to_variant(),
+            RapierBodyParam::Dominance => (self.get_dominance_group() as i64).to_variant(),
+            RapierBodyParam::SolverMembership => (self.solver_membership as i64).to_variant(),
+            RapierBodyParam::SolverFilter => (self.solver_filter as i64).to_variant(),
+            RapierBodyParam::FrictionCombineRule => {
+                (self.get_friction_combine_rule() as i64).to_variant()
+            }
+            RapierBodyParam::RestitutionCombineRule => {
+                (self.get_restitution_combine_rule() as i64).to_variant()
+            }
         }
     }
 
+    fn get_friction_combine_rule(&self) -> CoefficientCombineRule {
+        friction_combine_rule(self.friction, self.friction_combine_rule)
+    }
+
+    fn get_restitution_combine_rule(&self) -> CoefficientCombineRule {
+        restitution_combine_rule(self.bounce, self.restitution_combine_rule)
+    }
+
     pub fn set_mode(
         &mut self,
         p_mode: BodyMode,
@@ -1309,6 +1498,7 @@ impl RapierBody {
         }
         let prev_mode = self.base.mode;
         self.base.mode = p_mode;
+        self.apply_locked_axes(true, physics_engine);
         let rid = self.base.get_rid();
         if let Some(space) = physics_spaces.get_mut(&self.base.get_space()) {
             match p_mode {
@@ -1487,6 +1677,27 @@ impl RapierBody {
         self.ccd_enabled
     }
 
+    #[cfg(feature = "dim3")]
+    pub fn set_axis_lock(
+        &mut self,
+        axis: BodyAxis,
+        lock: bool,
+        physics_engine: &mut PhysicsEngine,
+    ) {
+        let axis = axis.ord() as u8;
+        if lock {
+            self.locked_axes |= axis;
+        } else {
+            self.locked_axes &= !axis;
+        }
+        self.apply_locked_axes(true, physics_engine);
+    }
+
+    #[cfg(feature = "dim3")]
+    pub fn is_axis_locked(&self, axis: BodyAxis) -> bool {
+        self.locked_axes & axis.ord() as u8 != 0
+    }
+
     pub fn update_mass_properties(
         &mut self,
         force_update: bool,
@@ -1749,6 +1960,7 @@ impl RapierBody {
         physics_engine: &mut PhysicsEngine,
         physics_spaces: &mut PhysicsSpaces,
     ) {
+        self.apply_dominance_group(physics_engine);
         if self.base.is_space_valid() && self.base.mode.ord() >= BodyMode::KINEMATIC.ord() {
             if !self.can_sleep {
                 self.set_can_sleep(false, physics_engine);
@@ -1813,10 +2025,25 @@ impl RapierBody {
             }
         }
     }
+
+    #[cfg(feature = "serde-serialize")]
+    pub fn load_state(&mut self, mut state: RapierBody, physics_engine: &mut PhysicsEngine) {
+        // Callbacks and direct state come from Godot, keep the current ones
+        std::mem::swap(
+            &mut state.body_state_callback,
+            &mut self.body_state_callback,
+        );
+        std::mem::swap(&mut state.fi_callback_data, &mut self.fi_callback_data);
+        std::mem::swap(&mut state.direct_state, &mut self.direct_state);
+        std::mem::swap(self, &mut state);
+        self.base.load_merged_shapes(&state.base, physics_engine);
+        // The handles are owned by the loaded state now
+        state.base.reset_handles();
+    }
 }
 // We won't use the pointers between threads, so it should be safe.
 unsafe impl Sync for RapierBody {}
-//#[cfg_attr(feature = "serde-serialize", typetag::serde)]
+#[cfg_attr(feature = "serde-serialize", typetag::serde)]
 impl IRapierCollisionObject for RapierBody {
     fn get_base(&self) -> &RapierCollisionObject {
         &self.base
@@ -1980,7 +2207,18 @@ impl IRapierCollisionObject for RapierBody {
         let handle =
             self.base
                 .create_shape(shape, p_shape_index, mat, physics_engine, physics_shapes);
-        self.init_collider(handle, self.base.get_space_handle(), physics_engine);
+        match self.base.chunk_colliders.get(&p_shape_index) {
+            Some(chunk_colliders) => {
+                for collider_handle in chunk_colliders {
+                    self.init_collider(
+                        *collider_handle,
+                        self.base.get_space_handle(),
+                        physics_engine,
+                    );
+                }
+            }
+            None => self.init_collider(handle, self.base.get_space_handle(), physics_engine),
+        }
         handle
     }
 
@@ -2003,11 +2241,15 @@ impl IRapierCollisionObject for RapierBody {
 
     fn init_material(&self) -> Material {
         Material {
-            friction: self.friction,
-            restitution: self.bounce,
+            friction: self.friction.abs(),
+            restitution: self.bounce.abs(),
+            friction_combine_rule: Some(self.get_friction_combine_rule()),
+            restitution_combine_rule: Some(self.get_restitution_combine_rule()),
             contact_skin: self.contact_skin,
             collision_layer: self.base.get_collision_layer(),
             collision_mask: self.base.get_collision_mask(),
+            solver_membership: Some(self.solver_membership),
+            solver_filter: Some(self.solver_filter),
         }
     }
 
@@ -3,6 +3,7 @@ use godot::engine::physics_server_2d::*;
 #[cfg(feature = "dim3")]
 use godot::engine::physics_server_3d::*;
 use godot::prelude::*;
+use hashbrown::HashMap;
 use rapier::dynamics::RigidBodyHandle;
 use rapier::geometry::ColliderHandle;
 use servers::rapier_physics_server_extra::PhysicsShapes;
@@ -13,7 +14,7 @@ use super::rapier_body::RapierBody;
 use crate::rapier_wrapper::prelude::*;
 use crate::types::*;
 use crate::*;
-//#[cfg_attr(feature = "serde-serialize", typetag::serde(tag = "type"))]
+#[cfg_attr(feature = "serde-serialize", typetag::serde(tag = "type"))]
 pub trait IRapierCollisionObject: Sync {
     fn get_base(&self) -> &RapierCollisionObject;
     fn get_mut_base(&mut self) -> &mut RapierCollisionObject;
@@ -136,8 +137,64 @@ impl Default for CollisionObjectShape {
         }
     }
 }
-// TODO deserialize
-#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+#[cfg(feature = "dim2")]
+pub(super) fn vector_to_cell(vector: Vector) -> [i32; 3] {
+    [vector.x as i32, vector.y as i32, 0]
+}
+#[cfg(feature = "dim3")]
+pub(super) fn vector_to_cell(vector: Vector) -> [i32; 3] {
+    [vector.x as i32, vector.y as i32, vector.z as i32]
+}
+// The cell and the ones around it
+fn neighbour_cells(cell: [i32; 3]) -> impl Iterator<Item = [i32; 3]> {
+    #[cfg(feature = "dim2")]
+    let z_offsets = 0..=0;
+    #[cfg(feature = "dim3")]
+    let z_offsets = -1..=1;
+    z_offsets.flat_map(move |z| {
+        (-1..=1).flat_map(move |y| {
+            (-1..=1).map(move |x| {
+                [
+                    cell[0].saturating_add(x),
+                    cell[1].saturating_add(y),
+                    cell[2].saturating_add(z),
+                ]
+            })
+        })
+    })
+}
+// Merged rectangles of the same size, with their shape index and local rect by cell
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct MergedGrid {
+    pub origin: Vector,
+    pub cell_size: Vector,
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(with = "crate::types::serde_map_as_entries")
+    )]
+    pub cells: HashMap<[i32; 3], (usize, Rect)>,
+}
+// Single collider replacing the axis aligned rectangle shapes of a static body
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct MergedShapes {
+    pub collider_handle: ColliderHandle,
+    pub shape_handle: ShapeHandle,
+    // Merged shape indexes with their local rect. The first index is stored in the collider user data.
+    pub shapes: Vec<(usize, Rect)>,
+    pub grids: Vec<MergedGrid>,
+}
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct RapierCollisionObject {
     collision_object_type: CollisionObjectType,
     rid: Rid,
@@ -150,12 +207,40 @@ pub struct RapierCollisionObject {
     inv_transform: Transform,
     collision_mask: u32,
     collision_layer: u32,
-    // TODO serialize this
-    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(with = "crate::types::serde_engine_enum")
+    )]
     pub(crate) mode: BodyMode,
     body_handle: RigidBodyHandle,
     space_handle: WorldHandle,
     pub(crate) area_detection_counter: u32,
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    pub(crate) merged_shapes: Option<MergedShapes>,
+    // Colliders of the bodies shapes that are made of chunks, one per chunk, by shape index. The
+    // shape collider handle is the first valid one.
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(default, with = "crate::types::serde_map_as_entries")
+    )]
+    pub(crate) chunk_colliders: HashMap<usize, Vec<ColliderHandle>>,
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(default = "default_collision_priority")
+    )]
+    collision_priority: real,
+}
+fn default_collision_priority() -> real {
+    1.0
+}
+// Stored after the shape index in the user data of the merged collider
+const MERGED_COLLIDER_PART: u64 = u32::MAX as u64;
+fn first_valid_collider(collider_handles: &[ColliderHandle]) -> ColliderHandle {
+    collider_handles
+        .iter()
+        .copied()
+        .find(|collider_handle| *collider_handle != ColliderHandle::invalid())
+        .unwrap_or(ColliderHandle::invalid())
 }
 impl Default for RapierCollisionObject {
     fn default() -> Self {
@@ -184,11 +269,14 @@ impl RapierCollisionObject {
             body_handle: RigidBodyHandle::invalid(),
             space_handle: WorldHandle::default(),
             area_detection_counter: 0,
+            merged_shapes: None,
+            chunk_colliders: HashMap::default(),
+            collision_priority: default_collision_priority(),
         }
     }
 
     pub(super) fn create_shape(
-        &self,
+        &mut self,
         shape: CollisionObjectShape,
         p_shape_index: usize,
         mat: Material,
@@ -208,6 +296,28 @@ impl RapierCollisionObject {
             self.set_collider_user_data(&mut user_data, p_shape_index);
             match self.collision_object_type {
                 CollisionObjectType::Body => {
+                    let chunk_handles = shape_object.get_base().get_chunk_handles().to_vec();
+                    if !chunk_handles.is_empty() {
+                        let chunk_colliders: Vec<ColliderHandle> = chunk_handles
+                            .iter()
+                            .enumerate()
+                            .map(|(chunk_index, chunk_handle)| {
+                                self.create_chunk_collider(
+                                    *chunk_handle,
+                                    p_shape_index,
+                                    chunk_index,
+                                    &mat,
+                                    physics_engine,
+                                )
+                            })
+                            .collect();
+                        handle = first_valid_collider(&chunk_colliders);
+                        self.chunk_colliders.insert(p_shape_index, chunk_colliders);
+                        return handle;
+                    }
+                    let shape_handle = shape_object
+                        .get_mut_base()
+                        .get_solid_handle(self.is_dynamic(), physics_engine);
                     handle = physics_engine.collider_create_solid(
                         self.space_handle,
                         shape_handle,
@@ -229,11 +339,45 @@ impl RapierCollisionObject {
         handle
     }
 
+    pub(super) fn create_chunk_collider(
+        &self,
+        chunk_handle: ShapeHandle,
+        p_shape_index: usize,
+        p_chunk_index: usize,
+        mat: &Material,
+        physics_engine: &mut PhysicsEngine,
+    ) -> ColliderHandle {
+        if chunk_handle == ShapeHandle::default() {
+            return ColliderHandle::invalid();
+        }
+        let mut user_data = UserData::default();
+        self.set_chunk_collider_user_data(&mut user_data, p_shape_index, p_chunk_index);
+        physics_engine.collider_create_solid(
+            self.space_handle,
+            chunk_handle,
+            mat,
+            self.body_handle,
+            &user_data,
+        )
+    }
+
     pub(super) fn update_shapes_indexes(&mut self, physics_engine: &mut PhysicsEngine) {
         if !self.is_valid() {
             return;
         }
         for (shape_index, shape) in self.shapes.iter().enumerate() {
+            if let Some(chunk_colliders) = self.chunk_colliders.get(&shape_index) {
+                for (chunk_index, collider_handle) in chunk_colliders.iter().enumerate() {
+                    let mut user_data = UserData::default();
+                    self.set_chunk_collider_user_data(&mut user_data, shape_index, chunk_index);
+                    physics_engine.collider_set_user_data(
+                        self.space_handle,
+                        *collider_handle,
+                        &user_data,
+                    );
+                }
+                continue;
+            }
             let mut user_data = UserData::default();
             self.set_collider_user_data(&mut user_data, shape_index);
             physics_engine.collider_set_user_data(
@@ -249,56 +393,149 @@ impl RapierCollisionObject {
         physics_engine: &mut PhysicsEngine,
         physics_spaces: &mut PhysicsSpaces,
     ) {
-        if let Some(space) = physics_spaces.get_mut(&self.space) {
-            for (i, shape) in self.shapes.iter_mut().enumerate() {
-                if shape.collider_handle == ColliderHandle::invalid() {
-                    // skip
-                    continue;
-                }
-                if self.area_detection_counter > 0 {
-                    // Keep track of body information for delayed removal
-                    space.add_removed_collider(
-                        shape.collider_handle,
-                        self.rid,
-                        self.instance_id,
-                        i,
-                        self.collision_object_type,
-                    );
-                }
-                physics_engine.collider_destroy(self.space_handle, shape.collider_handle);
-                shape.collider_handle = ColliderHandle::invalid();
-            }
+        if !physics_spaces.contains_key(&self.space) {
+            return;
+        }
+        for i in 0..self.shapes.len() {
+            let shape = self.shapes[i];
+            self.shapes[i].collider_handle =
+                self.destroy_shape(shape, i, physics_spaces, physics_engine);
         }
     }
 
     pub(super) fn destroy_shape(
-        &self,
+        &mut self,
         shape: CollisionObjectShape,
         p_shape_index: usize,
         physics_spaces: &mut PhysicsSpaces,
         physics_engine: &mut PhysicsEngine,
     ) -> ColliderHandle {
-        if shape.collider_handle != ColliderHandle::invalid() {
-            if self.area_detection_counter > 0 {
-                if let Some(space) = physics_spaces.get_mut(&self.space) {
-                    // Keep track of body information for delayed removal
-                    space.add_removed_collider(
-                        shape.collider_handle,
-                        self.rid,
-                        self.instance_id,
-                        p_shape_index,
-                        self.get_type(),
-                    );
-                }
+        if let Some(chunk_colliders) = self.chunk_colliders.remove(&p_shape_index) {
+            for collider_handle in chunk_colliders {
+                self.destroy_collider(
+                    collider_handle,
+                    p_shape_index,
+                    physics_spaces,
+                    physics_engine,
+                );
             }
-            physics_engine.collider_destroy(self.space_handle, shape.collider_handle);
+            return ColliderHandle::invalid();
         }
+        self.destroy_collider(
+            shape.collider_handle,
+            p_shape_index,
+            physics_spaces,
+            physics_engine,
+        );
         ColliderHandle::invalid()
     }
 
+    pub(super) fn destroy_collider(
+        &self,
+        collider_handle: ColliderHandle,
+        p_shape_index: usize,
+        physics_spaces: &mut PhysicsSpaces,
+        physics_engine: &mut PhysicsEngine,
+    ) {
+        if collider_handle == ColliderHandle::invalid() {
+            return;
+        }
+        if self.area_detection_counter > 0
+            && let Some(space) = physics_spaces.get_mut(&self.space)
+        {
+            // Keep track of body information for delayed removal
+            space.add_removed_collider(
+                collider_handle,
+                self.rid,
+                self.instance_id,
+                p_shape_index,
+                self.collision_object_type,
+            );
+        }
+        physics_engine.collider_destroy(self.space_handle, collider_handle);
+    }
+
+    // Shape handle and global transform to test a collider of this object against, from the shape
+    // index in its user data. The merged collider is tested as a whole with its compound.
+    pub fn get_collider_shape(
+        &self,
+        p_shape_index: usize,
+        physics_shapes: &PhysicsShapes,
+    ) -> Option<(ShapeHandle, Transform)> {
+        if let Some(merged_shapes) = &self.merged_shapes
+            && merged_shapes.shapes.first().map(|shape| shape.0) == Some(p_shape_index)
+        {
+            return Some((merged_shapes.shape_handle, self.transform));
+        }
+        let shape = physics_shapes.get(&self.get_shape(p_shape_index))?;
+        Some((
+            shape.get_handle(),
+            self.transform * self.get_shape_transform(p_shape_index),
+        ))
+    }
+
+    // Finds the merged shape closest to a global point, so that the right shape is reported
+    pub fn get_shape_index_at(&self, p_shape_index: usize, p_point: Vector) -> usize {
+        let Some(merged_shapes) = &self.merged_shapes else {
+            return p_shape_index;
+        };
+        if merged_shapes.shapes.first().map(|shape| shape.0) != Some(p_shape_index) {
+            return p_shape_index;
+        }
+        // The point is on the merged collider, so the closest shape is in its cell or a neighbour one
+        let local_point = self.inv_transform * p_point;
+        let mut best_shape_index = p_shape_index;
+        let mut best_distance = real::MAX;
+        for grid in merged_shapes.grids.iter() {
+            let cell = vector_to_cell(((local_point - grid.origin) / grid.cell_size).floor());
+            for neighbour_cell in neighbour_cells(cell) {
+                let Some((shape_index, rect)) = grid.cells.get(&neighbour_cell) else {
+                    continue;
+                };
+                let closest_point = local_point.clamp(rect.position, rect.end());
+                let distance = closest_point.distance_squared_to(local_point);
+                if distance < best_distance {
+                    best_distance = distance;
+                    best_shape_index = *shape_index;
+                }
+            }
+        }
+        best_shape_index
+    }
+
+    // Destroys the merged collider and returns the indexes of the shapes it replaced
+    pub(super) fn destroy_merged_shapes(
+        &mut self,
+        physics_spaces: &mut PhysicsSpaces,
+        physics_engine: &mut PhysicsEngine,
+    ) -> Vec<usize> {
+        let Some(merged_shapes) = self.merged_shapes.take() else {
+            return Vec::new();
+        };
+        let shape_indices: Vec<usize> = merged_shapes.shapes.iter().map(|shape| shape.0).collect();
+        if merged_shapes.collider_handle != ColliderHandle::invalid() {
+            if self.area_detection_counter > 0
+                && let Some(space) = physics_spaces.get_mut(&self.space)
+            {
+                // Keep track of body information for delayed removal
+                space.add_removed_collider(
+                    merged_shapes.collider_handle,
+                    self.rid,
+                    self.instance_id,
+                    shape_indices[0],
+                    self.collision_object_type,
+                );
+            }
+            physics_engine.collider_destroy(self.space_handle, merged_shapes.collider_handle);
+        }
+        physics_engine.shape_destroy(merged_shapes.shape_handle);
+        shape_indices
+    }
+
     pub(super) fn update_shape_transform(
         &self,
         shape: &CollisionObjectShape,
+        p_shape_index: usize,
         physics_engine: &mut PhysicsEngine,
         physics_shapes: &mut PhysicsShapes,
     ) {
@@ -306,26 +543,109 @@ impl RapierCollisionObject {
             return;
         }
         if let Some(rapier_shape) = physics_shapes.get_mut(&shape.shape) {
-            let shape_handle = rapier_shape.get_handle();
+            let mut shape_handle = rapier_shape.get_handle();
             if shape_handle == ShapeHandle::default() {
                 godot_error!("Rapier shape is invalid");
                 return;
             }
-            let scale = transform_scale(&self.transform);
-            let mut shape_info = shape_info_from_body_shape(shape_handle, shape.xform);
-            shape_info.scale = vector_to_rapier(vector_to_godot(shape_info.scale) * scale);
-            let position = shape_info
-                .transform
-                .translation
-                .vector
-                .component_mul(&vector_to_rapier(scale));
-            shape_info.transform.translation.vector = position;
+            if let Some(chunk_colliders) = self.chunk_colliders.get(&p_shape_index) {
+                for (collider_handle, chunk_handle) in chunk_colliders
+                    .iter()
+                    .zip(rapier_shape.get_base().get_chunk_handles())
+                {
+                    if *collider_handle == ColliderHandle::invalid() {
+                        continue;
+                    }
+                    physics_engine.collider_set_transform(
+                        self.space_handle,
+                        *collider_handle,
+                        self.get_collider_shape_info(*chunk_handle, &shape.xform),
+                    );
+                }
+                return;
+            }
+            if self.collision_object_type == CollisionObjectType::Body {
+                shape_handle = rapier_shape
+                    .get_mut_base()
+                    .get_solid_handle(self.is_dynamic(), physics_engine);
+            }
             physics_engine.collider_set_transform(
                 self.space_handle,
                 shape.collider_handle,
-                shape_info,
+                self.get_collider_shape_info(shape_handle, &shape.xform),
+            );
+        }
+    }
+
+    fn get_collider_shape_info(&self, shape_handle: ShapeHandle, xform: &Transform) -> ShapeInfo {
+        let scale = transform_scale(&self.transform);
+        let mut shape_info = shape_info_from_body_shape(shape_handle, *xform);
+        shape_info.scale = vector_to_rapier(vector_to_godot(shape_info.scale) * scale);
+        let position = shape_info
+            .transform
+            .translation
+            .vector
+            .component_mul(&vector_to_rapier(scale));
+        shape_info.transform.translation.vector = position;
+        shape_info
+    }
+
+    // Recreates the colliders of the given chunks of a shape made of chunks and returns the
+    // colliders that were created
+    pub(super) fn replace_chunk_colliders(
+        &mut self,
+        p_shape_index: usize,
+        p_chunk_indices: &[usize],
+        mat: &Material,
+        physics_engine: &mut PhysicsEngine,
+        physics_shapes: &PhysicsShapes,
+        physics_spaces: &mut PhysicsSpaces,
+    ) -> Vec<ColliderHandle> {
+        let mut created_colliders = Vec::new();
+        let shape = self.shapes[p_shape_index];
+        let Some(rapier_shape) = physics_shapes.get(&shape.shape) else {
+            return created_colliders;
+        };
+        let chunk_handles = rapier_shape.get_base().get_chunk_handles();
+        let Some(mut chunk_colliders) = self.chunk_colliders.remove(&p_shape_index) else {
+            return created_colliders;
+        };
+        if chunk_colliders.len() < chunk_handles.len() {
+            chunk_colliders.resize(chunk_handles.len(), ColliderHandle::invalid());
+        }
+        for chunk_index in p_chunk_indices {
+            let (Some(chunk_handle), Some(collider_handle)) = (
+                chunk_handles.get(*chunk_index),
+                chunk_colliders.get(*chunk_index).copied(),
+            ) else {
+                continue;
+            };
+            self.destroy_collider(
+                collider_handle,
+                p_shape_index,
+                physics_spaces,
+                physics_engine,
+            );
+            let collider_handle = self.create_chunk_collider(
+                *chunk_handle,
+                p_shape_index,
+                *chunk_index,
+                mat,
+                physics_engine,
             );
+            chunk_colliders[*chunk_index] = collider_handle;
+            if collider_handle != ColliderHandle::invalid() {
+                physics_engine.collider_set_transform(
+                    self.space_handle,
+                    collider_handle,
+                    self.get_collider_shape_info(*chunk_handle, &shape.xform),
+                );
+                created_colliders.push(collider_handle);
+            }
         }
+        self.shapes[p_shape_index].collider_handle = first_valid_collider(&chunk_colliders);
+        self.chunk_colliders.insert(p_shape_index, chunk_colliders);
+        created_colliders
     }
 
     pub(super) fn update_transform(&mut self, physics_engine: &mut PhysicsEngine) {
@@ -350,6 +670,7 @@ impl RapierCollisionObject {
     ) {
         // previous space
         if self.is_space_valid() {
+            self.destroy_merged_shapes(physics_spaces, physics_engine);
             if self.is_body_valid() {
                 // This call also destroys the colliders
                 physics_engine.body_destroy(self.space_handle, self.body_handle);
@@ -433,6 +754,99 @@ impl RapierCollisionObject {
         self.body_handle
     }
 
+    pub(crate) fn set_body_handle(&mut self, body_handle: RigidBodyHandle) {
+        self.body_handle = body_handle;
+    }
+
+    // Used when the world is replaced, the handles are relinked afterwards
+    pub(crate) fn reset_handles(&mut self) {
+        self.body_handle = RigidBodyHandle::invalid();
+        // The merged shape handle is not part of the world, keep it to relink the collider
+        if let Some(merged_shapes) = &mut self.merged_shapes {
+            merged_shapes.collider_handle = ColliderHandle::invalid();
+        }
+        self.chunk_colliders.clear();
+        for shape in &mut self.shapes {
+            shape.collider_handle = ColliderHandle::invalid();
+        }
+    }
+
+    // Links a collider of a replaced world to its shape, using the collider user data
+    pub(crate) fn relink_collider(
+        &mut self,
+        collider_handle: ColliderHandle,
+        user_data: &UserData,
+    ) {
+        let (_, shape_index) = Self::get_collider_user_data(user_data);
+        if shape_index >= self.shapes.len() {
+            return;
+        }
+        if Self::is_merged_collider(user_data) {
+            if let Some(merged_shapes) = &mut self.merged_shapes {
+                merged_shapes.collider_handle = collider_handle;
+            }
+            return;
+        }
+        let Some(chunk_index) = Self::get_collider_chunk_index(user_data) else {
+            self.shapes[shape_index].collider_handle = collider_handle;
+            return;
+        };
+        let chunk_colliders = self.chunk_colliders.entry(shape_index).or_default();
+        if chunk_colliders.len() <= chunk_index {
+            chunk_colliders.resize(chunk_index + 1, ColliderHandle::invalid());
+        }
+        chunk_colliders[chunk_index] = collider_handle;
+        self.shapes[shape_index].collider_handle = first_valid_collider(chunk_colliders);
+    }
+
+    // Drops the merged shapes that have no collider in the world, their shapes kept their own
+    pub(crate) fn release_unlinked_merged_shapes(&mut self, physics_engine: &mut PhysicsEngine) {
+        if let Some(merged_shapes) = &self.merged_shapes
+            && merged_shapes.collider_handle == ColliderHandle::invalid()
+        {
+            physics_engine.shape_destroy(merged_shapes.shape_handle);
+            self.merged_shapes = None;
+        }
+    }
+
+    // Merged shapes own a shape handle outside of the world. When a saved state replaces this one,
+    // the replaced handle is released and the loaded one, which may no longer exist, is created
+    // again from the merged rects.
+    pub(crate) fn load_merged_shapes(
+        &mut self,
+        replaced: &RapierCollisionObject,
+        physics_engine: &mut PhysicsEngine,
+    ) {
+        let replaced_handle = replaced
+            .merged_shapes
+            .as_ref()
+            .map(|merged_shapes| merged_shapes.shape_handle);
+        if let Some(merged_shapes) = &mut self.merged_shapes
+            && Some(merged_shapes.shape_handle) != replaced_handle
+        {
+            let boxes: Vec<_> = merged_shapes
+                .shapes
+                .iter()
+                .map(|(_, rect)| {
+                    (
+                        vector_to_rapier((rect.position + rect.end()) * 0.5),
+                        vector_to_rapier(rect.size * 0.5),
+                    )
+                })
+                .collect();
+            merged_shapes.shape_handle = physics_engine.shape_create_box_compound(&boxes);
+        }
+        if let Some(replaced_handle) = replaced_handle
+            && self
+                .merged_shapes
+                .as_ref()
+                .map(|merged_shapes| merged_shapes.shape_handle)
+                != Some(replaced_handle)
+        {
+            physics_engine.shape_destroy(replaced_handle);
+        }
+    }
+
     pub fn set_canvas_instance_id(&mut self, p_canvas_instance_id: u64) {
         self.canvas_instance_id = p_canvas_instance_id;
     }
@@ -446,8 +860,41 @@ impl RapierCollisionObject {
         r_user_data.part2 = p_shape_index as u64;
     }
 
+    // Colliders of a chunk of a shape store the chunk after the shape index
+    pub fn set_chunk_collider_user_data(
+        &self,
+        r_user_data: &mut UserData,
+        p_shape_index: usize,
+        p_chunk_index: usize,
+    ) {
+        self.set_collider_user_data(r_user_data, p_shape_index);
+        r_user_data.part2 |= (p_chunk_index as u64 + 1) << 32;
+    }
+
     pub fn get_collider_user_data(p_user_data: &UserData) -> (Rid, usize) {
-        (Rid::new(p_user_data.part1), p_user_data.part2 as usize)
+        (
+            Rid::new(p_user_data.part1),
+            (p_user_data.part2 & u32::MAX as u64) as usize,
+        )
+    }
+
+    pub fn get_collider_chunk_index(p_user_data: &UserData) -> Option<usize> {
+        if Self::is_merged_collider(p_user_data) {
+            return None;
+        }
+        (p_user_data.part2 >> 32)
+            .checked_sub(1)
+            .map(|chunk_index| chunk_index as usize)
+    }
+
+    // The merged collider stores the first merged shape index
+    pub fn set_merged_collider_user_data(&self, r_user_data: &mut UserData, p_shape_index: usize) {
+        self.set_collider_user_data(r_user_data, p_shape_index);
+        r_user_data.part2 |= MERGED_COLLIDER_PART << 32;
+    }
+
+    pub fn is_merged_collider(p_user_data: &UserData) -> bool {
+        p_user_data.part2 >> 32 == MERGED_COLLIDER_PART
     }
 
     pub fn get_type(&self) -> CollisionObjectType {
@@ -564,10 +1011,35 @@ impl RapierCollisionObject {
         self.collision_layer
     }
 
+    pub fn set_collision_priority(&mut self, p_priority: real) {
+        self.collision_priority = p_priority;
+    }
+
+    pub fn get_collision_priority(&self) -> real {
+        self.collision_priority
+    }
+
+    // Priority 1 is the default dominance group, each extra point of priority dominates one more group
+    pub fn get_collision_priority_dominance(&self) -> i32 {
+        self.collision_priority.round() as i32 - 1
+    }
+
     pub fn get_mode(&self) -> BodyMode {
         self.mode
     }
 
+    pub fn is_dynamic(&self) -> bool {
+        self.mode.ord() >= BodyMode::RIGID.ord()
+    }
+
+    pub fn uses_convex_decomposition(&self, physics_shapes: &PhysicsShapes) -> bool {
+        self.shapes.iter().any(|shape| {
+            physics_shapes
+                .get(&shape.shape)
+                .is_some_and(|shape| shape.get_base().uses_convex_decomposition())
+        })
+    }
+
     pub fn set_pickable(&mut self, p_pickable: bool) {
         self.pickable = p_pickable;
     }
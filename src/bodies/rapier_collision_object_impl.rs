@@ -1,32 +1,255 @@
-use godot::builtin::Rid;
+#[cfg(feature = "dim2")]
+use godot::engine::physics_server_2d::*;
+#[cfg(feature = "dim3")]
+use godot::engine::physics_server_3d::*;
+use godot::prelude::*;
+use hashbrown::HashMap;
+use hashbrown::HashSet;
 use rapier::geometry::ColliderHandle;
 
+use super::rapier_collision_object::vector_to_cell;
 use super::rapier_collision_object::CollisionObjectShape;
 use super::rapier_collision_object::IRapierCollisionObject;
+use super::rapier_collision_object::MergedGrid;
+use super::rapier_collision_object::MergedShapes;
 use super::rapier_collision_object::RapierCollisionObject;
-use crate::rapier_wrapper::prelude::PhysicsEngine;
+use crate::rapier_wrapper::prelude::*;
 use crate::servers::rapier_physics_server_extra::PhysicsShapes;
 use crate::servers::rapier_physics_server_extra::PhysicsSpaces;
-use crate::types::Transform;
+use crate::types::*;
+#[cfg(feature = "dim2")]
+const RECTANGLE_SHAPE_TYPE: ShapeType = ShapeType::RECTANGLE;
+#[cfg(feature = "dim3")]
+const RECTANGLE_SHAPE_TYPE: ShapeType = ShapeType::BOX;
+// Scale of a transform that has no rotation or skew
+#[cfg(feature = "dim2")]
+fn axis_aligned_scale(transform: &Transform) -> Option<Vector> {
+    if transform.a.y != 0.0 || transform.b.x != 0.0 {
+        return None;
+    }
+    Some(Vector::new(transform.a.x.abs(), transform.b.y.abs()))
+}
+#[cfg(feature = "dim3")]
+fn axis_aligned_scale(transform: &Transform) -> Option<Vector> {
+    let basis = &transform.basis;
+    if basis.rows[0].y != 0.0
+        || basis.rows[0].z != 0.0
+        || basis.rows[1].x != 0.0
+        || basis.rows[1].z != 0.0
+        || basis.rows[2].x != 0.0
+        || basis.rows[2].y != 0.0
+    {
+        return None;
+    }
+    Some(Vector::new(
+        basis.rows[0].x.abs(),
+        basis.rows[1].y.abs(),
+        basis.rows[2].z.abs(),
+    ))
+}
+// Greedily merges occupied cells into boxes, first along x, then y, then z.
+// Returns the cells of each box.
+fn merge_cells<T>(cells: &HashMap<[i32; 3], T>) -> Vec<Vec<[i32; 3]>> {
+    let mut sorted_cells: Vec<[i32; 3]> = cells.keys().copied().collect();
+    sorted_cells.sort_by_key(|cell| [cell[2], cell[1], cell[0]]);
+    let mut visited = HashSet::new();
+    let is_free = |visited: &HashSet<[i32; 3]>, cell: [i32; 3]| {
+        cells.contains_key(&cell) && !visited.contains(&cell)
+    };
+    let mut boxes = Vec::new();
+    for [x, y, z] in sorted_cells {
+        if !is_free(&visited, [x, y, z]) {
+            continue;
+        }
+        let mut end_x = x + 1;
+        while is_free(&visited, [end_x, y, z]) {
+            end_x += 1;
+        }
+        let mut end_y = y + 1;
+        while (x..end_x).all(|cell_x| is_free(&visited, [cell_x, end_y, z])) {
+            end_y += 1;
+        }
+        let mut end_z = z + 1;
+        while (y..end_y)
+            .all(|cell_y| (x..end_x).all(|cell_x| is_free(&visited, [cell_x, cell_y, end_z])))
+        {
+            end_z += 1;
+        }
+        let mut cell_box = Vec::new();
+        for cell_z in z..end_z {
+            for cell_y in y..end_y {
+                for cell_x in x..end_x {
+                    visited.insert([cell_x, cell_y, cell_z]);
+                    cell_box.push([cell_x, cell_y, cell_z]);
+                }
+            }
+        }
+        boxes.push(cell_box);
+    }
+    boxes
+}
 impl RapierCollisionObject {
+    // Replaces the merged collider with one collider per shape again
+    pub(super) fn unmerge_shapes(
+        collision_object: &mut dyn IRapierCollisionObject,
+        physics_engine: &mut PhysicsEngine,
+        physics_shapes: &mut PhysicsShapes,
+        physics_spaces: &mut PhysicsSpaces,
+    ) {
+        let shape_indices = collision_object
+            .get_mut_base()
+            .destroy_merged_shapes(physics_spaces, physics_engine);
+        for i in shape_indices {
+            let shape = collision_object.get_base().shapes[i];
+            if shape.disabled || shape.collider_handle != ColliderHandle::invalid() {
+                continue;
+            }
+            collision_object.get_mut_base().shapes[i].collider_handle =
+                collision_object.create_shape(shape, i, physics_engine, physics_shapes);
+            collision_object.get_base().update_shape_transform(
+                &collision_object.get_base().shapes[i],
+                i,
+                physics_engine,
+                physics_shapes,
+            );
+        }
+    }
+
+    // Merges the axis aligned rectangle shapes of a static body that lie on the same grid into
+    // as few boxes as possible, all in a single collider. Returns the number of merged shapes.
+    pub(super) fn merge_rectangle_shapes(
+        collision_object: &mut dyn IRapierCollisionObject,
+        physics_engine: &mut PhysicsEngine,
+        physics_shapes: &mut PhysicsShapes,
+        physics_spaces: &mut PhysicsSpaces,
+    ) -> i32 {
+        RapierCollisionObject::unmerge_shapes(
+            collision_object,
+            physics_engine,
+            physics_shapes,
+            physics_spaces,
+        );
+        let base = collision_object.get_base();
+        if base.get_mode() != BodyMode::STATIC || !base.is_valid() {
+            godot_error!("Only static bodies in a space can merge their shapes");
+            return 0;
+        }
+        // Rectangles are grouped by size, each group with its own grid
+        let mut groups: Vec<(Vector, Vector, HashMap<[i32; 3], (usize, Rect)>)> = Vec::new();
+        for (i, shape) in base.shapes.iter().enumerate() {
+            if shape.disabled
+                || shape.one_way_collision
+                || shape.collider_handle == ColliderHandle::invalid()
+            {
+                continue;
+            }
+            let Some(rapier_shape) = physics_shapes.get(&shape.shape) else {
+                continue;
+            };
+            if rapier_shape.get_type() != RECTANGLE_SHAPE_TYPE
+                || rapier_shape.get_base().get_border_radius() > 0.0
+            {
+                continue;
+            }
+            let Some(scale) = axis_aligned_scale(&shape.xform) else {
+                continue;
+            };
+            let Ok(half_extents) = rapier_shape.get_data().try_to::<Vector>() else {
+                continue;
+            };
+            let size = half_extents * scale * 2.0;
+            let rect = Rect::new(shape.xform.origin - size * 0.5, size);
+            let group_index = match groups
+                .iter()
+                .position(|group| group.0.is_equal_approx(size))
+            {
+                Some(group_index) => group_index,
+                None => {
+                    groups.push((size, rect.position, HashMap::default()));
+                    groups.len() - 1
+                }
+            };
+            let (size, grid_origin, cells) = &mut groups[group_index];
+            let cell_position = ((rect.position - *grid_origin) / *size).round();
+            if !(*grid_origin + cell_position * *size).is_equal_approx(rect.position) {
+                continue;
+            }
+            cells.insert(vector_to_cell(cell_position), (i, rect));
+        }
+        let mut boxes = Vec::new();
+        let mut merged_shapes = Vec::new();
+        for (_, _, cells) in groups.iter() {
+            for cell_box in merge_cells(cells) {
+                // Cells are sorted, so the first and last cells are the box corners
+                let min = cells[&cell_box[0]].1.position;
+                let max = cells[&cell_box[cell_box.len() - 1]].1.end();
+                for cell in cell_box.iter() {
+                    merged_shapes.push(cells[cell]);
+                }
+                boxes.push((
+                    vector_to_rapier((min + max) * 0.5),
+                    vector_to_rapier((max - min) * 0.5),
+                ));
+            }
+        }
+        if merged_shapes.len() < 2 {
+            return 0;
+        }
+        let shape_handle = physics_engine.shape_create_box_compound(&boxes);
+        let mut user_data = UserData::default();
+        base.set_merged_collider_user_data(&mut user_data, merged_shapes[0].0);
+        let collider_handle = physics_engine.collider_create_solid(
+            base.get_space_handle(),
+            shape_handle,
+            &collision_object.init_material(),
+            base.get_body_handle(),
+            &user_data,
+        );
+        let mut shape_info = shape_info_from_body_shape(shape_handle, Transform::default());
+        shape_info.scale = vector_to_rapier(transform_scale(&base.get_transform()));
+        physics_engine.collider_set_transform(base.get_space_handle(), collider_handle, shape_info);
+        for (i, _) in merged_shapes.iter() {
+            let shape = collision_object.get_base().shapes[*i];
+            collision_object.get_mut_base().shapes[*i].collider_handle = collision_object
+                .get_mut_base()
+                .destroy_shape(shape, *i, physics_spaces, physics_engine);
+        }
+        let merged_shape_count = merged_shapes.len() as i32;
+        let grids = groups
+            .into_iter()
+            .map(|(cell_size, origin, cells)| MergedGrid {
+                origin,
+                cell_size,
+                cells,
+            })
+            .collect();
+        collision_object.get_mut_base().merged_shapes = Some(MergedShapes {
+            collider_handle,
+            shape_handle,
+            shapes: merged_shapes,
+            grids,
+        });
+        merged_shape_count
+    }
+
     pub(super) fn recreate_shapes(
         collision_object: &mut dyn IRapierCollisionObject,
         physics_engine: &mut PhysicsEngine,
         physics_shapes: &mut PhysicsShapes,
         physics_spaces: &mut PhysicsSpaces,
     ) {
+        collision_object
+            .get_mut_base()
+            .destroy_merged_shapes(physics_spaces, physics_engine);
         for i in 0..collision_object.get_base().get_shape_count() as usize {
             if collision_object.get_base().shapes[i].disabled {
                 continue;
             }
-            if collision_object.get_base().shapes[i].collider_handle != ColliderHandle::invalid() {
-                collision_object.get_mut_base().shapes[i].collider_handle =
-                    collision_object.get_base().destroy_shape(
-                        collision_object.get_base().shapes[i],
-                        i,
-                        physics_spaces,
-                        physics_engine,
-                    );
+            let shape = collision_object.get_base().shapes[i];
+            if shape.collider_handle != ColliderHandle::invalid() {
+                collision_object.get_mut_base().shapes[i].collider_handle = collision_object
+                    .get_mut_base()
+                    .destroy_shape(shape, i, physics_spaces, physics_engine);
             }
             collision_object.get_mut_base().shapes[i].collider_handle = collision_object
                 .create_shape(
@@ -37,6 +260,7 @@ impl RapierCollisionObject {
                 );
             collision_object.get_base().update_shape_transform(
                 &collision_object.get_base().shapes[i],
+                i,
                 physics_engine,
                 physics_shapes,
             );
@@ -52,6 +276,12 @@ impl RapierCollisionObject {
         physics_spaces: &mut PhysicsSpaces,
         physics_shapes: &mut PhysicsShapes,
     ) {
+        RapierCollisionObject::unmerge_shapes(
+            collision_object,
+            physics_engine,
+            physics_shapes,
+            physics_spaces,
+        );
         let mut shape = CollisionObjectShape {
             xform: p_transform,
             shape: p_shape,
@@ -61,14 +291,12 @@ impl RapierCollisionObject {
             collider_handle: ColliderHandle::invalid(),
         };
         if !shape.disabled {
-            shape.collider_handle = collision_object.create_shape(
-                shape,
-                collision_object.get_base().shapes.len(),
-                physics_engine,
-                physics_shapes,
-            );
+            let shape_index = collision_object.get_base().shapes.len();
+            shape.collider_handle =
+                collision_object.create_shape(shape, shape_index, physics_engine, physics_shapes);
             collision_object.get_base().update_shape_transform(
                 &shape,
+                shape_index,
                 physics_engine,
                 physics_shapes,
             );
@@ -91,29 +319,77 @@ impl RapierCollisionObject {
         physics_shapes: &mut PhysicsShapes,
         physics_spaces: &mut PhysicsSpaces,
     ) {
+        RapierCollisionObject::unmerge_shapes(
+            collision_object,
+            physics_engine,
+            physics_shapes,
+            physics_spaces,
+        );
         for i in 0..collision_object.get_base().shapes.len() {
             let shape = collision_object.get_base().shapes[i];
             if shape.shape != p_shape || shape.disabled {
                 continue;
             }
-            if collision_object.get_base().shapes[i].collider_handle != ColliderHandle::invalid() {
+            if shape.collider_handle != ColliderHandle::invalid() {
                 collision_object.get_mut_base().shapes[i].collider_handle = collision_object
-                    .get_base()
+                    .get_mut_base()
                     .destroy_shape(shape, i, physics_spaces, physics_engine);
             }
-            collision_object.get_mut_base().shapes[i].collider_handle =
-                collision_object.get_base().create_shape(
+            collision_object.get_mut_base().shapes[i].collider_handle = collision_object
+                .create_shape(
                     collision_object.get_base().shapes[i],
                     i,
-                    collision_object.init_material(),
                     physics_engine,
                     physics_shapes,
                 );
             collision_object.get_base().update_shape_transform(
                 &collision_object.get_base().shapes[i],
+                i,
+                physics_engine,
+                physics_shapes,
+            );
+        }
+        collision_object.shapes_changed(physics_engine, physics_spaces);
+    }
+
+    // Replaces only the colliders of the chunks that changed when a body shape is made of chunks
+    pub(crate) fn shape_chunks_changed(
+        collision_object: &mut dyn IRapierCollisionObject,
+        p_shape: Rid,
+        p_chunk_indices: &[usize],
+        physics_engine: &mut PhysicsEngine,
+        physics_shapes: &mut PhysicsShapes,
+        physics_spaces: &mut PhysicsSpaces,
+    ) {
+        let base = collision_object.get_base();
+        let shape_indices: Vec<usize> = (0..base.shapes.len())
+            .filter(|i| base.shapes[*i].shape == p_shape && !base.shapes[*i].disabled)
+            .collect();
+        if collision_object.get_body().is_none()
+            || !base.is_valid()
+            || shape_indices
+                .iter()
+                .any(|i| !base.chunk_colliders.contains_key(i))
+        {
+            collision_object.shape_changed(p_shape, physics_engine, physics_shapes, physics_spaces);
+            return;
+        }
+        let mat = collision_object.init_material();
+        let space_handle = base.get_space_handle();
+        for i in shape_indices {
+            let created_colliders = collision_object.get_mut_base().replace_chunk_colliders(
+                i,
+                p_chunk_indices,
+                &mat,
                 physics_engine,
                 physics_shapes,
+                physics_spaces,
             );
+            if let Some(body) = collision_object.get_body() {
+                for collider_handle in created_colliders {
+                    body.init_collider(collider_handle, space_handle, physics_engine);
+                }
+            }
         }
         collision_object.shapes_changed(physics_engine, physics_spaces);
     }
@@ -125,13 +401,19 @@ impl RapierCollisionObject {
         physics_spaces: &mut PhysicsSpaces,
         physics_shapes: &mut PhysicsShapes,
     ) {
+        RapierCollisionObject::unmerge_shapes(
+            collision_object,
+            physics_engine,
+            physics_shapes,
+            physics_spaces,
+        );
         if p_index >= collision_object.get_base().shapes.len() {
             return;
         }
-        let shape = &collision_object.get_base().shapes[p_index];
+        let shape = collision_object.get_base().shapes[p_index];
         if !shape.disabled {
-            collision_object.get_base().destroy_shape(
-                *shape,
+            collision_object.get_mut_base().destroy_shape(
+                shape,
                 p_index,
                 physics_spaces,
                 physics_engine,
@@ -145,6 +427,18 @@ impl RapierCollisionObject {
                 .remove_owner(collision_object.get_base().get_rid());
         }
         collision_object.get_mut_base().shapes.remove(p_index);
+        // Chunk colliders of the following shapes move down with their shape
+        let chunk_colliders = std::mem::take(&mut collision_object.get_mut_base().chunk_colliders);
+        collision_object.get_mut_base().chunk_colliders = chunk_colliders
+            .into_iter()
+            .map(|(shape_index, colliders)| {
+                if shape_index > p_index {
+                    (shape_index - 1, colliders)
+                } else {
+                    (shape_index, colliders)
+                }
+            })
+            .collect();
         if collision_object.get_base().is_space_valid() {
             collision_object.shapes_changed(physics_engine, physics_spaces);
         }
@@ -161,17 +455,19 @@ impl RapierCollisionObject {
         physics_spaces: &mut PhysicsSpaces,
         physics_shapes: &mut PhysicsShapes,
     ) {
+        RapierCollisionObject::unmerge_shapes(
+            collision_object,
+            physics_engine,
+            physics_shapes,
+            physics_spaces,
+        );
         if p_index >= collision_object.get_base().shapes.len() {
             return;
         }
-        collision_object.get_mut_base().shapes[p_index].collider_handle =
-            collision_object.get_base().destroy_shape(
-                collision_object.get_base().shapes[p_index],
-                p_index,
-                physics_spaces,
-                physics_engine,
-            );
         let shape = collision_object.get_base().shapes[p_index];
+        collision_object.get_mut_base().shapes[p_index].collider_handle = collision_object
+            .get_mut_base()
+            .destroy_shape(shape, p_index, physics_spaces, physics_engine);
         if let Some(shape) = physics_shapes.get_mut(&shape.shape) {
             shape
                 .get_mut_base()
@@ -184,15 +480,16 @@ impl RapierCollisionObject {
                 .add_owner(collision_object.get_base().get_rid());
         }
         if !shape.disabled {
-            collision_object.get_base().create_shape(
-                shape,
-                p_index,
-                collision_object.init_material(),
-                physics_engine,
-                physics_shapes,
-            );
+            collision_object.get_mut_base().shapes[p_index].collider_handle = collision_object
+                .create_shape(
+                    collision_object.get_base().shapes[p_index],
+                    p_index,
+                    physics_engine,
+                    physics_shapes,
+                );
             collision_object.get_base().update_shape_transform(
-                &shape,
+                &collision_object.get_base().shapes[p_index],
+                p_index,
                 physics_engine,
                 physics_shapes,
             );
@@ -210,14 +507,23 @@ impl RapierCollisionObject {
         physics_spaces: &mut PhysicsSpaces,
         physics_shapes: &mut PhysicsShapes,
     ) {
+        RapierCollisionObject::unmerge_shapes(
+            collision_object,
+            physics_engine,
+            physics_shapes,
+            physics_spaces,
+        );
         if p_index >= collision_object.get_base().shapes.len() {
             return;
         }
         collision_object.get_mut_base().shapes[p_index].xform = p_transform;
         let shape = &collision_object.get_base().shapes[p_index];
-        collision_object
-            .get_base()
-            .update_shape_transform(shape, physics_engine, physics_shapes);
+        collision_object.get_base().update_shape_transform(
+            shape,
+            p_index,
+            physics_engine,
+            physics_shapes,
+        );
         if collision_object.get_base().is_space_valid() {
             collision_object.shapes_changed(physics_engine, physics_spaces);
         }
@@ -231,6 +537,12 @@ impl RapierCollisionObject {
         physics_spaces: &mut PhysicsSpaces,
         physics_shapes: &mut PhysicsShapes,
     ) {
+        RapierCollisionObject::unmerge_shapes(
+            collision_object,
+            physics_engine,
+            physics_shapes,
+            physics_spaces,
+        );
         if p_index >= collision_object.get_base().shapes.len() {
             return;
         }
@@ -241,19 +553,15 @@ impl RapierCollisionObject {
         }
         if shape.disabled {
             collision_object.get_mut_base().shapes[p_index].collider_handle = collision_object
-                .get_base()
+                .get_mut_base()
                 .destroy_shape(shape, p_index, physics_spaces, physics_engine);
         }
         if !shape.disabled {
-            collision_object.get_base().create_shape(
-                shape,
-                p_index,
-                collision_object.init_material(),
-                physics_engine,
-                physics_shapes,
-            );
+            collision_object.get_mut_base().shapes[p_index].collider_handle =
+                collision_object.create_shape(shape, p_index, physics_engine, physics_shapes);
             collision_object.get_base().update_shape_transform(
-                &shape,
+                &collision_object.get_base().shapes[p_index],
+                p_index,
                 physics_engine,
                 physics_shapes,
             );
@@ -263,3 +571,55 @@ impl RapierCollisionObject {
         }
     }
 }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn cells_of(cells: &[[i32; 3]]) -> HashMap<[i32; 3], ()> {
+        cells.iter().map(|cell| (*cell, ())).collect()
+    }
+    #[test]
+    fn merge_cells_empty() {
+        assert!(merge_cells(&cells_of(&[])).is_empty());
+    }
+    #[test]
+    fn merge_cells_full_rectangle_is_one_box() {
+        let mut cells = Vec::new();
+        for y in 0..2 {
+            for x in -1..2 {
+                cells.push([x, y, 0]);
+            }
+        }
+        let boxes = merge_cells(&cells_of(&cells));
+        assert_eq!(boxes.len(), 1);
+        assert_eq!(boxes[0].len(), 6);
+    }
+    #[test]
+    fn merge_cells_merges_along_x_first() {
+        let boxes = merge_cells(&cells_of(&[[0, 0, 0], [1, 0, 0], [0, 1, 0]]));
+        assert_eq!(boxes, vec![vec![[0, 0, 0], [1, 0, 0]], vec![[0, 1, 0]]]);
+    }
+    #[test]
+    fn merge_cells_merges_along_z() {
+        let boxes = merge_cells(&cells_of(&[[0, 0, 0], [0, 0, 1], [0, 0, 2]]));
+        assert_eq!(boxes, vec![vec![[0, 0, 0], [0, 0, 1], [0, 0, 2]]]);
+    }
+    #[test]
+    fn merge_cells_covers_each_cell_once() {
+        let cells = [
+            [0, 0, 0],
+            [2, 0, 0],
+            [1, 1, 0],
+            [2, 1, 0],
+            [0, 2, 0],
+            [1, 2, 0],
+            [2, 2, 0],
+            [5, 5, 0],
+        ];
+        let boxes = merge_cells(&cells_of(&cells));
+        let mut merged: Vec<[i32; 3]> = boxes.into_iter().flatten().collect();
+        merged.sort();
+        let mut expected = cells.to_vec();
+        expected.sort();
+        assert_eq!(merged, expected);
+    }
+}
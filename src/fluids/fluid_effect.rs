@@ -1,4 +1,14 @@
 use godot::prelude::*;
+use rapier::math::Real;
+
+use super::fluid_effect_elasticity::FluidEffectElasticity;
+use super::fluid_effect_surface_tension_akinci::FluidEffectTensionAkinci;
+use super::fluid_effect_surface_tension_he::FluidEffectTensionHE;
+use super::fluid_effect_surface_tension_wcsph::FluidEffectSurfaceTensionWCSPH;
+use super::fluid_effect_viscosity_artificial::FluidEffectViscosityArtificial;
+use super::fluid_effect_viscosity_dfsph::FluidEffectViscosityDFSPH;
+use super::fluid_effect_viscosity_xsph::FluidEffectViscosityXSPH;
+use crate::rapier_wrapper::prelude::*;
 pub trait IFluidEffect {
     fn get_fluid_effect_type(&self) -> FluidEffectType;
 }
@@ -35,3 +45,258 @@ impl IFluidEffect for FluidEffect {
         self.fluid_effect_type.clone()
     }
 }
+// Plain data version of the effect resources, used for serialization
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[derive(Clone, Debug)]
+pub enum FluidEffectExport {
+    Elasticity {
+        young_modulus: real,
+        poisson_ratio: real,
+        nonlinear_strain: bool,
+    },
+    SurfaceTensionAkinci {
+        fluid_tension_coefficient: real,
+        boundary_adhesion_coefficient: real,
+    },
+    SurfaceTensionHe {
+        fluid_tension_coefficient: real,
+        boundary_adhesion_coefficient: real,
+    },
+    SurfaceTensionWcsph {
+        fluid_tension_coefficient: real,
+        boundary_adhesion_coefficient: real,
+    },
+    ViscosityArtificial {
+        fluid_viscosity_coefficient: real,
+        boundary_adhesion_coefficient: real,
+    },
+    ViscosityDfsph {
+        fluid_viscosity_coefficient: real,
+    },
+    ViscosityXsph {
+        fluid_viscosity_coefficient: real,
+        boundary_adhesion_coefficient: real,
+    },
+}
+impl FluidEffectExport {
+    pub fn from_resource(resource: &Gd<Resource>) -> Option<Self> {
+        if let Ok(effect) = resource.clone().try_cast::<FluidEffectElasticity>() {
+            let effect = effect.bind();
+            return Some(FluidEffectExport::Elasticity {
+                young_modulus: effect.young_modulus,
+                poisson_ratio: effect.poisson_ratio,
+                nonlinear_strain: effect.nonlinear_strain,
+            });
+        }
+        if let Ok(effect) = resource.clone().try_cast::<FluidEffectTensionAkinci>() {
+            let effect = effect.bind();
+            return Some(FluidEffectExport::SurfaceTensionAkinci {
+                fluid_tension_coefficient: effect.fluid_tension_coefficient,
+                boundary_adhesion_coefficient: effect.boundary_adhesion_coefficient,
+            });
+        }
+        if let Ok(effect) = resource.clone().try_cast::<FluidEffectTensionHE>() {
+            let effect = effect.bind();
+            return Some(FluidEffectExport::SurfaceTensionHe {
+                fluid_tension_coefficient: effect.fluid_tension_coefficient,
+                boundary_adhesion_coefficient: effect.boundary_adhesion_coefficient,
+            });
+        }
+        if let Ok(effect) = resource
+            .clone()
+            .try_cast::<FluidEffectSurfaceTensionWCSPH>()
+        {
+            let effect = effect.bind();
+            return Some(FluidEffectExport::SurfaceTensionWcsph {
+                fluid_tension_coefficient: effect.fluid_tension_coefficient,
+                boundary_adhesion_coefficient: effect.boundary_adhesion_coefficient,
+            });
+        }
+        if let Ok(effect) = resource
+            .clone()
+            .try_cast::<FluidEffectViscosityArtificial>()
+        {
+            let effect = effect.bind();
+            return Some(FluidEffectExport::ViscosityArtificial {
+                fluid_viscosity_coefficient: effect.fluid_viscosity_coefficient,
+                boundary_adhesion_coefficient: effect.boundary_adhesion_coefficient,
+            });
+        }
+        if let Ok(effect) = resource.clone().try_cast::<FluidEffectViscosityDFSPH>() {
+            let effect = effect.bind();
+            return Some(FluidEffectExport::ViscosityDfsph {
+                fluid_viscosity_coefficient: effect.fluid_viscosity_coefficient,
+            });
+        }
+        if let Ok(effect) = resource.clone().try_cast::<FluidEffectViscosityXSPH>() {
+            let effect = effect.bind();
+            return Some(FluidEffectExport::ViscosityXsph {
+                fluid_viscosity_coefficient: effect.fluid_viscosity_coefficient,
+                boundary_adhesion_coefficient: effect.boundary_adhesion_coefficient,
+            });
+        }
+        None
+    }
+
+    pub fn to_resource(&self) -> Gd<Resource> {
+        match *self {
+            FluidEffectExport::Elasticity {
+                young_modulus,
+                poisson_ratio,
+                nonlinear_strain,
+            } => {
+                let mut effect = FluidEffectElasticity::new_gd();
+                {
+                    let mut effect = effect.bind_mut();
+                    effect.young_modulus = young_modulus;
+                    effect.poisson_ratio = poisson_ratio;
+                    effect.nonlinear_strain = nonlinear_strain;
+                }
+                effect.upcast()
+            }
+            FluidEffectExport::SurfaceTensionAkinci {
+                fluid_tension_coefficient,
+                boundary_adhesion_coefficient,
+            } => {
+                let mut effect = FluidEffectTensionAkinci::new_gd();
+                {
+                    let mut effect = effect.bind_mut();
+                    effect.fluid_tension_coefficient = fluid_tension_coefficient;
+                    effect.boundary_adhesion_coefficient = boundary_adhesion_coefficient;
+                }
+                effect.upcast()
+            }
+            FluidEffectExport::SurfaceTensionHe {
+                fluid_tension_coefficient,
+                boundary_adhesion_coefficient,
+            } => {
+                let mut effect = FluidEffectTensionHE::new_gd();
+                {
+                    let mut effect = effect.bind_mut();
+                    effect.fluid_tension_coefficient = fluid_tension_coefficient;
+                    effect.boundary_adhesion_coefficient = boundary_adhesion_coefficient;
+                }
+                effect.upcast()
+            }
+            FluidEffectExport::SurfaceTensionWcsph {
+                fluid_tension_coefficient,
+                boundary_adhesion_coefficient,
+            } => {
+                let mut effect = FluidEffectSurfaceTensionWCSPH::new_gd();
+                {
+                    let mut effect = effect.bind_mut();
+                    effect.fluid_tension_coefficient = fluid_tension_coefficient;
+                    effect.boundary_adhesion_coefficient = boundary_adhesion_coefficient;
+                }
+                effect.upcast()
+            }
+            FluidEffectExport::ViscosityArtificial {
+                fluid_viscosity_coefficient,
+                boundary_adhesion_coefficient,
+            } => {
+                let mut effect = FluidEffectViscosityArtificial::new_gd();
+                {
+                    let mut effect = effect.bind_mut();
+                    effect.fluid_viscosity_coefficient = fluid_viscosity_coefficient;
+                    effect.boundary_adhesion_coefficient = boundary_adhesion_coefficient;
+                }
+                effect.upcast()
+            }
+            FluidEffectExport::ViscosityDfsph {
+                fluid_viscosity_coefficient,
+            } => {
+                let mut effect = FluidEffectViscosityDFSPH::new_gd();
+                effect.bind_mut().fluid_viscosity_coefficient = fluid_viscosity_coefficient;
+                effect.upcast()
+            }
+            FluidEffectExport::ViscosityXsph {
+                fluid_viscosity_coefficient,
+                boundary_adhesion_coefficient,
+            } => {
+                let mut effect = FluidEffectViscosityXSPH::new_gd();
+                {
+                    let mut effect = effect.bind_mut();
+                    effect.fluid_viscosity_coefficient = fluid_viscosity_coefficient;
+                    effect.boundary_adhesion_coefficient = boundary_adhesion_coefficient;
+                }
+                effect.upcast()
+            }
+        }
+    }
+
+    pub fn apply(
+        &self,
+        physics_engine: &mut PhysicsEngine,
+        world_handle: WorldHandle,
+        fluid_handle: HandleDouble,
+    ) {
+        match *self {
+            FluidEffectExport::Elasticity {
+                young_modulus,
+                poisson_ratio,
+                nonlinear_strain,
+            } => physics_engine.fluid_add_effect_elasticity(
+                world_handle,
+                fluid_handle,
+                young_modulus as Real,
+                poisson_ratio as Real,
+                nonlinear_strain,
+            ),
+            FluidEffectExport::SurfaceTensionAkinci {
+                fluid_tension_coefficient,
+                boundary_adhesion_coefficient,
+            } => physics_engine.fluid_add_effect_surface_tension_akinci(
+                world_handle,
+                fluid_handle,
+                fluid_tension_coefficient as Real,
+                boundary_adhesion_coefficient as Real,
+            ),
+            FluidEffectExport::SurfaceTensionHe {
+                fluid_tension_coefficient,
+                boundary_adhesion_coefficient,
+            } => physics_engine.fluid_add_effect_surface_tension_he(
+                world_handle,
+                fluid_handle,
+                fluid_tension_coefficient as Real,
+                boundary_adhesion_coefficient as Real,
+            ),
+            FluidEffectExport::SurfaceTensionWcsph {
+                fluid_tension_coefficient,
+                boundary_adhesion_coefficient,
+            } => physics_engine.fluid_add_effect_surface_tension_wcsph(
+                world_handle,
+                fluid_handle,
+                fluid_tension_coefficient as Real,
+                boundary_adhesion_coefficient as Real,
+            ),
+            FluidEffectExport::ViscosityArtificial {
+                fluid_viscosity_coefficient,
+                boundary_adhesion_coefficient,
+            } => physics_engine.fluid_add_effect_viscosity_artificial(
+                world_handle,
+                fluid_handle,
+                fluid_viscosity_coefficient as Real,
+                boundary_adhesion_coefficient as Real,
+            ),
+            FluidEffectExport::ViscosityDfsph {
+                fluid_viscosity_coefficient,
+            } => physics_engine.fluid_add_effect_viscosity_dfsph(
+                world_handle,
+                fluid_handle,
+                fluid_viscosity_coefficient as Real,
+            ),
+            FluidEffectExport::ViscosityXsph {
+                fluid_viscosity_coefficient,
+                boundary_adhesion_coefficient,
+            } => physics_engine.fluid_add_effect_viscosity_xsph(
+                world_handle,
+                fluid_handle,
+                fluid_viscosity_coefficient as Real,
+                boundary_adhesion_coefficient as Real,
+            ),
+        }
+    }
+}
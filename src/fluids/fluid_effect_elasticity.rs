@@ -6,11 +6,11 @@ use super::fluid_effect::IFluidEffect;
 #[class(base=Resource)]
 pub struct FluidEffectElasticity {
     #[export]
-    young_modulus: real,
+    pub(crate) young_modulus: real,
     #[export]
-    poisson_ratio: real,
+    pub(crate) poisson_ratio: real,
     #[export]
-    nonlinear_strain: bool,
+    pub(crate) nonlinear_strain: bool,
 
     base: Base<Resource>,
 }
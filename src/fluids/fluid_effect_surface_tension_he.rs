@@ -6,9 +6,9 @@ use super::fluid_effect::IFluidEffect;
 #[class(base=Resource)]
 pub struct FluidEffectTensionHE {
     #[export]
-    fluid_tension_coefficient: real,
+    pub(crate) fluid_tension_coefficient: real,
     #[export]
-    boundary_adhesion_coefficient: real,
+    pub(crate) boundary_adhesion_coefficient: real,
 
     base: Base<Resource>,
 }
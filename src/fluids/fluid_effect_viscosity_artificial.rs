@@ -6,9 +6,9 @@ use super::fluid_effect::IFluidEffect;
 #[class(base=Resource)]
 pub struct FluidEffectViscosityArtificial {
     #[export]
-    fluid_viscosity_coefficient: real,
+    pub(crate) fluid_viscosity_coefficient: real,
     #[export]
-    boundary_adhesion_coefficient: real,
+    pub(crate) boundary_adhesion_coefficient: real,
 
     base: Base<Resource>,
 }
@@ -6,7 +6,7 @@ use super::fluid_effect::IFluidEffect;
 #[class(base=Resource)]
 pub struct FluidEffectViscosityDFSPH {
     #[export]
-    fluid_viscosity_coefficient: real,
+    pub(crate) fluid_viscosity_coefficient: real,
 
     base: Base<Resource>,
 }
@@ -1,15 +1,51 @@
 use std::collections::VecDeque;
 
 use godot::prelude::*;
+#[cfg(feature = "serde-serialize")]
+use hashbrown::HashMap;
 
+use super::fluid_effect::FluidEffectExport;
 use crate::rapier_wrapper::prelude::*;
 use crate::types::Vector;
-//#[derive(Serialize, Deserialize, Debug)]
+#[cfg(feature = "serde-serialize")]
+mod serde_effects {
+    use godot::prelude::*;
+    use serde::Deserialize;
+    use serde::Serialize;
+
+    use super::FluidEffectExport;
+    pub fn serialize<S: serde::Serializer>(
+        effects: &Array<Gd<Resource>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let effects: Vec<FluidEffectExport> = effects
+            .iter_shared()
+            .filter_map(|effect| FluidEffectExport::from_resource(&effect))
+            .collect();
+        effects.serialize(serializer)
+    }
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Array<Gd<Resource>>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let effects = Vec::<FluidEffectExport>::deserialize(deserializer)?;
+        let mut array = Array::new();
+        for effect in effects {
+            array.push(effect.to_resource());
+        }
+        Ok(array)
+    }
+}
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct RapierFluid {
     rid: Rid,
     enabled: bool,
     density: f64,
     space: Rid,
+    #[cfg_attr(feature = "serde-serialize", serde(with = "serde_effects"))]
     effects: Array<Gd<Resource>>,
     fluid_handle: HandleDouble,
     points: Vec<Vector>,
@@ -99,6 +135,63 @@ impl RapierFluid {
         self.space
     }
 
+    pub fn get_fluid_handle(&self) -> HandleDouble {
+        self.fluid_handle
+    }
+
+    // Takes the imported state, the fluid handle is remapped to the one in the new world
+    #[cfg(feature = "serde-serialize")]
+    pub fn load_state(
+        &mut self,
+        mut state: RapierFluid,
+        space: Rid,
+        world_handle: WorldHandle,
+        fluid_handles: &HashMap<HandleDouble, HandleDouble>,
+        physics_engine: &mut PhysicsEngine,
+    ) {
+        state.rid = self.rid;
+        state.space = space;
+        state.world_handle = world_handle;
+        state.fluid_handle = fluid_handles
+            .get(&state.fluid_handle)
+            .copied()
+            .unwrap_or(invalid_handle_double());
+        *self = state;
+        // The particles in the world are the source of truth, an empty fluid has none
+        if self.fluid_handle == invalid_handle_double() {
+            self.clear_points();
+        } else {
+            self.points = physics_engine
+                .fluid_get_points(world_handle, self.fluid_handle)
+                .to_vec();
+            self.velocities = physics_engine
+                .fluid_get_velocities(world_handle, self.fluid_handle)
+                .to_vec();
+            self.accelerations = physics_engine
+                .fluid_get_accelerations(world_handle, self.fluid_handle)
+                .to_vec();
+        }
+        self.apply_effects(physics_engine);
+    }
+
+    pub fn clear_points(&mut self) {
+        self.points.clear();
+        self.velocities.clear();
+        self.accelerations.clear();
+    }
+
+    pub fn apply_effects(&self, physics_engine: &mut PhysicsEngine) {
+        if self.fluid_handle == invalid_handle_double() {
+            return;
+        }
+        physics_engine.fluid_clear_effects(self.world_handle, self.fluid_handle);
+        for effect in self.effects.iter_shared() {
+            if let Some(effect) = FluidEffectExport::from_resource(&effect) {
+                effect.apply(physics_engine, self.world_handle, self.fluid_handle);
+            }
+        }
+    }
+
     pub fn destroy_fluid(&mut self, physics_engine: &mut PhysicsEngine) {
         if self.fluid_handle != invalid_handle_double() {
             physics_engine.fluid_destroy(self.world_handle, self.fluid_handle);
@@ -60,6 +60,10 @@ impl RapierJointBase {
         self.handle
     }
 
+    pub(crate) fn set_handle(&mut self, handle: ImpulseJointHandle) {
+        self.handle = handle;
+    }
+
     pub fn get_space_handle(&self) -> WorldHandle {
         self.space_handle
     }
@@ -22,6 +22,8 @@ mod bodies;
 mod fluids;
 mod joints;
 mod rapier_wrapper;
+#[cfg(feature = "serde-serialize")]
+pub mod replay;
 mod servers;
 mod shapes;
 mod spaces;
@@ -42,6 +44,7 @@ unsafe impl ExtensionLibrary for RapierPhysicsExtensionLibrary {
                 servers::register_scene();
             }
             InitLevel::Servers => {
+                rapier_wrapper::logging::set_engine_logging(true);
                 servers::register_server();
             }
             _ => (),
@@ -55,6 +58,7 @@ unsafe impl ExtensionLibrary for RapierPhysicsExtensionLibrary {
             }
             InitLevel::Servers => {
                 servers::unregister_server();
+                rapier_wrapper::logging::set_engine_logging(false);
             }
             _ => (),
         }
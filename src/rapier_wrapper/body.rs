@@ -216,6 +216,12 @@ impl PhysicsEngine {
                     if mat.restitution >= 0.0 {
                         col.set_restitution(mat.restitution);
                     }
+                    if let Some(friction_combine_rule) = mat.friction_combine_rule {
+                        col.set_friction_combine_rule(friction_combine_rule);
+                    }
+                    if let Some(restitution_combine_rule) = mat.restitution_combine_rule {
+                        col.set_restitution_combine_rule(restitution_combine_rule);
+                    }
                     if mat.contact_skin >= 0.0 {
                         col.set_contact_skin(mat.contact_skin);
                     }
@@ -223,6 +229,14 @@ impl PhysicsEngine {
                         memberships: Group::from(mat.collision_layer),
                         filter: Group::from(mat.collision_mask),
                     });
+                    let mut solver_groups = col.solver_groups();
+                    if let Some(solver_membership) = mat.solver_membership {
+                        solver_groups.memberships = Group::from(solver_membership);
+                    }
+                    if let Some(solver_filter) = mat.solver_filter {
+                        solver_groups.filter = Group::from(solver_filter);
+                    }
+                    col.set_solver_groups(solver_groups);
                 }
             }
         }
@@ -327,6 +341,22 @@ impl PhysicsEngine {
         }
     }
 
+    pub fn body_set_dominance_group(
+        &mut self,
+        world_handle: WorldHandle,
+        body_handle: RigidBodyHandle,
+        dominance: i8,
+    ) {
+        if let Some(physics_world) = self.get_mut_world(world_handle)
+            && let Some(body) = physics_world
+                .physics_objects
+                .rigid_body_set
+                .get_mut(body_handle)
+        {
+            body.set_dominance_group(dominance);
+        }
+    }
+
     pub fn body_set_can_sleep(
         &mut self,
         world_handle: WorldHandle,
@@ -373,6 +403,23 @@ impl PhysicsEngine {
         }
     }
 
+    pub fn body_set_locked_axes(
+        &mut self,
+        world_handle: WorldHandle,
+        body_handle: RigidBodyHandle,
+        locked_axes: LockedAxes,
+        wake_up: bool,
+    ) {
+        if let Some(physics_world) = self.get_mut_world(world_handle)
+            && let Some(body) = physics_world
+                .physics_objects
+                .rigid_body_set
+                .get_mut(body_handle)
+        {
+            body.set_locked_axes(locked_axes, wake_up);
+        }
+    }
+
     pub fn body_set_mass_properties(
         &mut self,
         world_handle: WorldHandle,
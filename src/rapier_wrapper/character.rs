@@ -0,0 +1,147 @@
+use rapier::control::CharacterAutostep;
+use rapier::control::CharacterLength;
+use rapier::control::KinematicCharacterController;
+use rapier::prelude::*;
+
+use crate::rapier_wrapper::prelude::*;
+use crate::servers::rapier_physics_server_extra::PhysicsCollisionObjects;
+use crate::spaces::rapier_space::RapierSpace;
+// Options that are not set keep the rapier defaults
+#[derive(Default)]
+pub struct CharacterMoveOptions {
+    pub up: Option<Vector<Real>>,
+    pub offset: Option<Real>,
+    pub slide: Option<bool>,
+    pub autostep_max_height: Option<Real>,
+    pub autostep_min_width: Option<Real>,
+    pub autostep_include_dynamic_bodies: Option<bool>,
+    pub max_slope_climb_angle: Option<Real>,
+    pub min_slope_slide_angle: Option<Real>,
+    pub snap_to_ground: Option<Real>,
+}
+impl CharacterMoveOptions {
+    fn controller(&self) -> KinematicCharacterController {
+        let mut controller = KinematicCharacterController::default();
+        if let Some(up) = self.up
+            && let Some(up) = UnitVector::try_new(up, Real::EPSILON)
+        {
+            controller.up = up;
+        }
+        if let Some(offset) = self.offset {
+            controller.offset = CharacterLength::Absolute(offset);
+        }
+        if let Some(slide) = self.slide {
+            controller.slide = slide;
+        }
+        // Autostep is off by default, setting any of its options turns it on
+        if self.autostep_max_height.is_some()
+            || self.autostep_min_width.is_some()
+            || self.autostep_include_dynamic_bodies.is_some()
+        {
+            let mut autostep = CharacterAutostep::default();
+            if let Some(max_height) = self.autostep_max_height {
+                autostep.max_height = CharacterLength::Absolute(max_height);
+            }
+            if let Some(min_width) = self.autostep_min_width {
+                autostep.min_width = CharacterLength::Absolute(min_width);
+            }
+            if let Some(include_dynamic_bodies) = self.autostep_include_dynamic_bodies {
+                autostep.include_dynamic_bodies = include_dynamic_bodies;
+            }
+            controller.autostep = Some(autostep);
+        }
+        if let Some(max_slope_climb_angle) = self.max_slope_climb_angle {
+            controller.max_slope_climb_angle = max_slope_climb_angle;
+        }
+        if let Some(min_slope_slide_angle) = self.min_slope_slide_angle {
+            controller.min_slope_slide_angle = min_slope_slide_angle;
+        }
+        if let Some(snap_to_ground) = self.snap_to_ground {
+            controller.snap_to_ground = if snap_to_ground > 0.0 {
+                Some(CharacterLength::Absolute(snap_to_ground))
+            } else {
+                None
+            };
+        }
+        controller
+    }
+}
+pub struct CharacterCollisionInfo {
+    pub collider: ColliderHandle,
+    pub user_data: UserData,
+    pub pixel_position: Vector<Real>,
+    pub normal: Vector<Real>,
+    pub translation_applied: Vector<Real>,
+    pub translation_remaining: Vector<Real>,
+}
+#[derive(Default)]
+pub struct CharacterMoveResult {
+    pub translation: Vector<Real>,
+    pub grounded: bool,
+    pub is_sliding_down_slope: bool,
+    pub collisions: Vec<CharacterCollisionInfo>,
+}
+impl PhysicsEngine {
+    pub fn character_move(
+        &self,
+        world_handle: WorldHandle,
+        shape_info: ShapeInfo,
+        desired_translation: Vector<Real>,
+        options: &CharacterMoveOptions,
+        solver_groups: InteractionGroups,
+        dt: Real,
+        handle_excluded_info: &QueryExcludedInfo,
+        physics_collision_objects: &PhysicsCollisionObjects,
+        space: &RapierSpace,
+    ) -> CharacterMoveResult {
+        let mut result = CharacterMoveResult::default();
+        let Some(raw_shared_shape) = self.get_shape(shape_info.handle) else {
+            return result;
+        };
+        let Some(physics_world) = self.get_world(world_handle) else {
+            return result;
+        };
+        let shared_shape = scale_shape(raw_shared_shape, shape_info, self.shape_subdivisions);
+        // Colliders whose solver groups don't match the body's are moved through
+        let predicate = |handle: ColliderHandle, collider: &Collider| -> bool {
+            collider.solver_groups().test(solver_groups)
+                && !space.is_handle_excluded_callback(
+                    handle,
+                    &physics_world.get_collider_user_data(handle),
+                    handle_excluded_info,
+                    physics_collision_objects,
+                )
+        };
+        let mut filter = QueryFilter::new().exclude_sensors();
+        filter.predicate = Some(&predicate);
+        let collider_set = &physics_world.physics_objects.collider_set;
+        let movement = options.controller().move_shape(
+            dt,
+            &physics_world.physics_objects.rigid_body_set,
+            collider_set,
+            &physics_world.physics_objects.query_pipeline,
+            shared_shape.as_ref(),
+            &shape_info.transform,
+            desired_translation,
+            filter,
+            |collision| {
+                // The second witness and normal are local to the collider that was hit
+                let Some(collider) = collider_set.get(collision.handle) else {
+                    return;
+                };
+                result.collisions.push(CharacterCollisionInfo {
+                    collider: collision.handle,
+                    user_data: UserData::new(collider.user_data),
+                    pixel_position: (collider.position() * collision.hit.witness2).coords,
+                    normal: collider.position() * collision.hit.normal2.into_inner(),
+                    translation_applied: collision.translation_applied,
+                    translation_remaining: collision.translation_remaining,
+                });
+            },
+        );
+        result.translation = movement.translation;
+        result.grounded = movement.grounded;
+        result.is_sliding_down_slope = movement.is_sliding_down_slope;
+        result
+    }
+}
@@ -1,14 +1,15 @@
-use godot::log::godot_error;
 use rapier::prelude::*;
 use salva::integrations::rapier::ColliderSampling;
 use salva::object::Boundary;
 use salva::parry::either::Either::Left;
 use salva::parry::either::Either::Right;
 
+use crate::rapier_wrapper::logging::physics_error;
 use crate::rapier_wrapper::prelude::*;
-const SUBDIVISIONS: u32 = 20;
+// Default number of subdivisions used when a scaled shape has to be approximated
+pub const DEFAULT_SHAPE_SUBDIVISIONS: u32 = 20;
 #[cfg(feature = "dim2")]
-fn skew_polyline(vertices: &Vec<Point<Real>>, skew: Real) -> SharedShape {
+fn skew_points(vertices: &[Point<Real>], skew: Real) -> Vec<Point<Real>> {
     // Apply skew transformation to the vertices
     let mut skewed_vertices = Vec::new();
     for vertex in vertices {
@@ -16,6 +17,11 @@ fn skew_polyline(vertices: &Vec<Point<Real>>, skew: Real) -> SharedShape {
         skewed_vertex.x -= skewed_vertex.y * skew;
         skewed_vertices.push(skewed_vertex);
     }
+    skewed_vertices
+}
+#[cfg(feature = "dim2")]
+fn skew_polyline(vertices: &Vec<Point<Real>>, skew: Real) -> SharedShape {
+    let skewed_vertices = skew_points(vertices, skew);
     let len = vertices.len();
     let mut indices = vec![];
     for i in 0..len {
@@ -24,9 +30,185 @@ fn skew_polyline(vertices: &Vec<Point<Real>>, skew: Real) -> SharedShape {
     let collider = ColliderBuilder::convex_decomposition(&skewed_vertices, &indices);
     collider.shape
 }
+// Skew and non uniform scale don't commute with rotations, so rotated compound sub shapes are
+// turned into shapes in the compound frame first. Balls don't change when rotated.
+fn unrotate_sub_shape(
+    position: &Isometry<Real>,
+    shape: &SharedShape,
+    subdivisions: u32,
+) -> (Isometry<Real>, SharedShape) {
+    let rotation = position.rotation;
+    if rotation.angle() == 0.0 {
+        return (*position, shape.clone());
+    }
+    let rotate = |points: &[Point<Real>]| -> Vec<Point<Real>> {
+        points.iter().map(|point| rotation * *point).collect()
+    };
+    let rotated_shape = match shape.shape_type() {
+        ShapeType::Ball => Some(shape.clone()),
+        ShapeType::Triangle => shape.as_triangle().map(|triangle| {
+            let vertices = rotate(triangle.vertices());
+            SharedShape::triangle(vertices[0], vertices[1], vertices[2])
+        }),
+        ShapeType::Segment => shape
+            .as_segment()
+            .map(|segment| SharedShape::segment(rotation * segment.a, rotation * segment.b)),
+        ShapeType::RoundTriangle => shape.as_round_triangle().map(|round_triangle| {
+            let vertices = rotate(round_triangle.inner_shape.vertices());
+            SharedShape::round_triangle(
+                vertices[0],
+                vertices[1],
+                vertices[2],
+                round_triangle.border_radius,
+            )
+        }),
+        ShapeType::Polyline => shape.as_polyline().map(|polyline| {
+            SharedShape::polyline(
+                rotate(polyline.vertices()),
+                Some(polyline.indices().to_vec()),
+            )
+        }),
+        ShapeType::HalfSpace => shape
+            .as_halfspace()
+            .map(|halfspace| SharedShape::halfspace(rotation * halfspace.normal)),
+        ShapeType::Compound => shape
+            .as_compound()
+            .map(|compound| rotate_compound(compound, rotation)),
+        _ => rotate_shape_points(shape, rotate, subdivisions),
+    };
+    unrotated_position(position, rotated_shape, shape)
+}
+// Shapes that are rotated as the hull of their 2D outline
+#[cfg(feature = "dim2")]
+fn rotate_shape_points(
+    shape: &SharedShape,
+    rotate: impl Fn(&[Point<Real>]) -> Vec<Point<Real>>,
+    subdivisions: u32,
+) -> Option<SharedShape> {
+    match shape.shape_type() {
+        ShapeType::Cuboid => shape
+            .as_cuboid()
+            .and_then(|cuboid| SharedShape::convex_hull(&rotate(&cuboid.to_polyline()))),
+        ShapeType::Capsule => shape.as_capsule().and_then(|capsule| {
+            SharedShape::convex_hull(&rotate(&capsule.to_polyline(subdivisions)))
+        }),
+        ShapeType::ConvexPolygon => shape
+            .as_convex_polygon()
+            .and_then(|convex_polygon| SharedShape::convex_hull(&rotate(convex_polygon.points()))),
+        ShapeType::RoundCuboid => shape.as_round_cuboid().and_then(|round_cuboid| {
+            SharedShape::round_convex_hull(
+                &rotate(&round_cuboid.inner_shape.to_polyline()),
+                round_cuboid.border_radius,
+            )
+        }),
+        ShapeType::RoundConvexPolygon => {
+            shape
+                .as_round_convex_polygon()
+                .and_then(|round_convex_polygon| {
+                    SharedShape::round_convex_hull(
+                        &rotate(round_convex_polygon.inner_shape.points()),
+                        round_convex_polygon.border_radius,
+                    )
+                })
+        }
+        _ => None,
+    }
+}
+// Shapes that are rotated as the hull or mesh of their 3D vertices
+#[cfg(feature = "dim3")]
+fn rotate_shape_points(
+    shape: &SharedShape,
+    rotate: impl Fn(&[Point<Real>]) -> Vec<Point<Real>>,
+    subdivisions: u32,
+) -> Option<SharedShape> {
+    match shape.shape_type() {
+        ShapeType::Cuboid => shape
+            .as_cuboid()
+            .and_then(|cuboid| SharedShape::convex_hull(&rotate(&cuboid.to_trimesh().0))),
+        ShapeType::Capsule => shape.as_capsule().and_then(|capsule| {
+            SharedShape::convex_hull(&rotate(
+                &capsule.to_trimesh(subdivisions, subdivisions / 2).0,
+            ))
+        }),
+        ShapeType::Cylinder => shape.as_cylinder().and_then(|cylinder| {
+            SharedShape::convex_hull(&rotate(&cylinder.to_trimesh(subdivisions).0))
+        }),
+        ShapeType::Cone => shape
+            .as_cone()
+            .and_then(|cone| SharedShape::convex_hull(&rotate(&cone.to_trimesh(subdivisions).0))),
+        ShapeType::ConvexPolyhedron => shape.as_convex_polyhedron().and_then(|convex_polyhedron| {
+            SharedShape::convex_hull(&rotate(convex_polyhedron.points()))
+        }),
+        ShapeType::RoundCuboid => shape.as_round_cuboid().and_then(|round_cuboid| {
+            SharedShape::round_convex_hull(
+                &rotate(&round_cuboid.inner_shape.to_trimesh().0),
+                round_cuboid.border_radius,
+            )
+        }),
+        ShapeType::RoundCylinder => shape.as_round_cylinder().and_then(|round_cylinder| {
+            SharedShape::round_convex_hull(
+                &rotate(&round_cylinder.inner_shape.to_trimesh(subdivisions).0),
+                round_cylinder.border_radius,
+            )
+        }),
+        ShapeType::RoundCone => shape.as_round_cone().and_then(|round_cone| {
+            SharedShape::round_convex_hull(
+                &rotate(&round_cone.inner_shape.to_trimesh(subdivisions).0),
+                round_cone.border_radius,
+            )
+        }),
+        ShapeType::RoundConvexPolyhedron => {
+            shape
+                .as_round_convex_polyhedron()
+                .and_then(|round_convex_polyhedron| {
+                    SharedShape::round_convex_hull(
+                        &rotate(round_convex_polyhedron.inner_shape.points()),
+                        round_convex_polyhedron.border_radius,
+                    )
+                })
+        }
+        ShapeType::TriMesh => shape.as_trimesh().map(|trimesh| {
+            SharedShape::trimesh_with_flags(
+                rotate(trimesh.vertices()),
+                trimesh.indices().to_vec(),
+                trimesh.flags(),
+            )
+        }),
+        ShapeType::HeightField => shape.as_heightfield().map(|heightfield| {
+            let (vertices, indices) = heightfield.to_trimesh();
+            SharedShape::trimesh(rotate(&vertices), indices)
+        }),
+        _ => None,
+    }
+}
+// The rotation is moved to the sub shape positions, which are unrotated in turn when they are used
+fn rotate_compound(compound: &Compound, rotation: Rotation<Real>) -> SharedShape {
+    let mut rotation_only = Isometry::<Real>::identity();
+    rotation_only.rotation = rotation;
+    SharedShape::compound(
+        compound
+            .shapes()
+            .iter()
+            .map(|(position, sub_shape)| (rotation_only * position, sub_shape.clone()))
+            .collect(),
+    )
+}
+fn unrotated_position(
+    position: &Isometry<Real>,
+    rotated_shape: Option<SharedShape>,
+    shape: &SharedShape,
+) -> (Isometry<Real>, SharedShape) {
+    let Some(rotated_shape) = rotated_shape else {
+        physics_error!("Shape type not supported for rotating in a compound");
+        return (*position, shape.clone());
+    };
+    let mut translation_only = Isometry::<Real>::identity();
+    translation_only.translation = position.translation;
+    (translation_only, rotated_shape)
+}
 // Function to skew a shape
 #[cfg(feature = "dim2")]
-pub fn skew_shape(shape: &SharedShape, skew: Real) -> SharedShape {
+pub fn skew_shape(shape: &SharedShape, skew: Real, subdivisions: u32) -> SharedShape {
     use godot::builtin::math::FloatExt;
     if skew.is_zero_approx() {
         return shape.clone();
@@ -37,8 +219,12 @@ pub fn skew_shape(shape: &SharedShape, skew: Real) -> SharedShape {
                 let shapes = compound.shapes();
                 let mut transformed_shapes = Vec::new();
                 for (position, sub_shape) in shapes.iter() {
-                    let skewed_sub_shape = skew_shape(sub_shape, skew);
-                    let transformed_position = *position;
+                    let (position, sub_shape) =
+                        unrotate_sub_shape(position, sub_shape, subdivisions);
+                    let skewed_sub_shape = skew_shape(&sub_shape, skew, subdivisions);
+                    let mut transformed_position = position;
+                    transformed_position.translation.vector.x -=
+                        transformed_position.translation.vector.y * skew;
                     transformed_shapes.push((transformed_position, skewed_sub_shape));
                 }
                 return SharedShape::compound(transformed_shapes);
@@ -46,7 +232,7 @@ pub fn skew_shape(shape: &SharedShape, skew: Real) -> SharedShape {
         }
         ShapeType::Ball => {
             if let Some(ball) = shape.as_ball() {
-                return skew_polyline(&ball.to_polyline(SUBDIVISIONS), skew);
+                return skew_polyline(&ball.to_polyline(subdivisions), skew);
             }
         }
         ShapeType::Cuboid => {
@@ -55,8 +241,12 @@ pub fn skew_shape(shape: &SharedShape, skew: Real) -> SharedShape {
             }
         }
         ShapeType::Polyline => {
+            // Keep the segments as they are, polylines are not closed shapes
             if let Some(polyline) = shape.as_polyline() {
-                return skew_polyline(&polyline.vertices().to_vec(), skew);
+                return SharedShape::polyline(
+                    skew_points(polyline.vertices(), skew),
+                    Some(polyline.indices().to_vec()),
+                );
             }
         }
         ShapeType::ConvexPolygon => {
@@ -64,22 +254,69 @@ pub fn skew_shape(shape: &SharedShape, skew: Real) -> SharedShape {
                 return skew_polyline(&convex_polygon.points().to_vec(), skew);
             }
         }
+        ShapeType::HalfSpace => {
+            // The normal is transformed by the inverse transpose of the skew
+            if let Some(halfspace) = shape.as_halfspace() {
+                let normal = halfspace.normal;
+                return SharedShape::halfspace(UnitVector::new_normalize(Vector::new(
+                    normal.x,
+                    normal.x * skew + normal.y,
+                )));
+            }
+        }
+        ShapeType::RoundCuboid => {
+            if let Some(round_cuboid) = shape.as_round_cuboid() {
+                let skewed_vertices = skew_points(&round_cuboid.inner_shape.to_polyline(), skew);
+                if let Some(shape) =
+                    SharedShape::round_convex_hull(&skewed_vertices, round_cuboid.border_radius)
+                {
+                    return shape;
+                }
+            }
+        }
+        ShapeType::RoundConvexPolygon => {
+            if let Some(round_convex_polygon) = shape.as_round_convex_polygon() {
+                let skewed_vertices = skew_points(round_convex_polygon.inner_shape.points(), skew);
+                if let Some(shape) = SharedShape::round_convex_hull(
+                    &skewed_vertices,
+                    round_convex_polygon.border_radius,
+                ) {
+                    return shape;
+                }
+            }
+        }
+        ShapeType::RoundTriangle => {
+            if let Some(round_triangle) = shape.as_round_triangle() {
+                let skewed_vertices = skew_points(round_triangle.inner_shape.vertices(), skew);
+                return SharedShape::round_triangle(
+                    skewed_vertices[0],
+                    skewed_vertices[1],
+                    skewed_vertices[2],
+                    round_triangle.border_radius,
+                );
+            }
+        }
         ShapeType::Capsule => {
             if let Some(capsule) = shape.as_capsule() {
-                return skew_polyline(&capsule.to_polyline(SUBDIVISIONS), skew);
+                return skew_polyline(&capsule.to_polyline(subdivisions), skew);
+            }
+        }
+        ShapeType::Triangle => {
+            if let Some(triangle) = shape.as_triangle() {
+                return skew_polyline(&triangle.vertices().to_vec(), skew);
             }
         }
         _ => {
-            godot_error!("Shape type not supported for skewing");
+            physics_error!("Shape type not supported for skewing");
         }
     }
     shape.clone()
 }
 #[cfg(feature = "dim2")]
-pub fn scale_shape(shape: &SharedShape, shape_info: ShapeInfo) -> SharedShape {
+pub fn scale_shape(shape: &SharedShape, shape_info: ShapeInfo, subdivisions: u32) -> SharedShape {
     let mut new_shape = shape.clone();
     if shape_info.skew != 0.0 {
-        new_shape = skew_shape(shape, shape_info.skew);
+        new_shape = skew_shape(shape, shape_info.skew, subdivisions);
     }
     let shape = new_shape;
     let scale = shape_info.scale;
@@ -89,7 +326,7 @@ pub fn scale_shape(shape: &SharedShape, shape_info: ShapeInfo) -> SharedShape {
     match shape.shape_type() {
         ShapeType::Ball => {
             if let Some(new_shape) = shape.as_ball() {
-                if let Some(new_shape) = new_shape.scaled(&scale, SUBDIVISIONS) {
+                if let Some(new_shape) = new_shape.scaled(&scale, subdivisions) {
                     match new_shape {
                         Left(shape) => return SharedShape::new(shape),
                         Right(shape) => return SharedShape::new(shape),
@@ -123,7 +360,7 @@ pub fn scale_shape(shape: &SharedShape, shape_info: ShapeInfo) -> SharedShape {
         }
         ShapeType::Capsule => {
             if let Some(new_shape) = shape.as_capsule() {
-                if let Some(new_shape) = new_shape.scaled(&scale, SUBDIVISIONS) {
+                if let Some(new_shape) = new_shape.scaled(&scale, subdivisions) {
                     match new_shape {
                         Left(shape) => return SharedShape::new(shape),
                         Right(shape) => return SharedShape::new(shape),
@@ -131,25 +368,71 @@ pub fn scale_shape(shape: &SharedShape, shape_info: ShapeInfo) -> SharedShape {
                 }
             }
         }
+        ShapeType::Triangle => {
+            if let Some(new_shape) = shape.as_triangle() {
+                return SharedShape::triangle(
+                    new_shape.a.coords.component_mul(&scale).into(),
+                    new_shape.b.coords.component_mul(&scale).into(),
+                    new_shape.c.coords.component_mul(&scale).into(),
+                );
+            }
+        }
+        ShapeType::RoundTriangle => {
+            if let Some(new_shape) = shape.as_round_triangle() {
+                let inner_shape = new_shape.inner_shape;
+                return SharedShape::round_triangle(
+                    inner_shape.a.coords.component_mul(&scale).into(),
+                    inner_shape.b.coords.component_mul(&scale).into(),
+                    inner_shape.c.coords.component_mul(&scale).into(),
+                    new_shape.border_radius,
+                );
+            }
+        }
+        ShapeType::RoundCuboid => {
+            if let Some(new_shape) = shape.as_round_cuboid() {
+                return SharedShape::new(RoundShape {
+                    inner_shape: new_shape.inner_shape.scaled(&scale),
+                    border_radius: new_shape.border_radius,
+                });
+            }
+        }
+        ShapeType::RoundConvexPolygon => {
+            if let Some(new_shape) = shape.as_round_convex_polygon() {
+                if let Some(inner_shape) = new_shape.inner_shape.clone().scaled(&scale) {
+                    return SharedShape::new(RoundShape {
+                        inner_shape,
+                        border_radius: new_shape.border_radius,
+                    });
+                }
+            }
+        }
         ShapeType::Compound => {
             if let Some(new_shape) = shape.as_compound() {
                 let new_shapes = new_shape.shapes();
                 let mut shapes_vec = Vec::new();
-                for shape in new_shapes {
-                    let new_shape = scale_shape(&shape.1, shape_info);
-                    shapes_vec.push((shape.0, new_shape));
+                // The skew was already applied to the whole compound
+                let mut sub_shape_info = shape_info;
+                sub_shape_info.skew = 0.0;
+                for (position, sub_shape) in new_shapes {
+                    let (mut new_position, sub_shape) =
+                        unrotate_sub_shape(position, sub_shape, subdivisions);
+                    // Sub shape positions are scaled too, eg. the offset of halfspaces
+                    new_position.translation.vector =
+                        new_position.translation.vector.component_mul(&scale);
+                    let new_shape = scale_shape(&sub_shape, sub_shape_info, subdivisions);
+                    shapes_vec.push((new_position, new_shape));
                 }
                 return SharedShape::compound(shapes_vec);
             }
         }
         _ => {
-            godot_error!("Shape type not supported for scaling");
+            physics_error!("Shape type not supported for scaling");
         }
     }
     shape.clone()
 }
 #[cfg(feature = "dim3")]
-pub fn scale_shape(shape: &SharedShape, shape_info: ShapeInfo) -> SharedShape {
+pub fn scale_shape(shape: &SharedShape, shape_info: ShapeInfo, subdivisions: u32) -> SharedShape {
     let scale = shape_info.scale;
     if scale.x == 1.0 && scale.y == 1.0 && scale.z == 1.0 {
         return shape.clone();
@@ -157,7 +440,7 @@ pub fn scale_shape(shape: &SharedShape, shape_info: ShapeInfo) -> SharedShape {
     match shape.shape_type() {
         ShapeType::Ball => {
             if let Some(new_shape) = shape.as_ball() {
-                if let Some(new_shape) = new_shape.scaled(&scale, SUBDIVISIONS) {
+                if let Some(new_shape) = new_shape.scaled(&scale, subdivisions) {
                     match new_shape {
                         Left(shape) => return SharedShape::new(shape),
                         Right(shape) => return SharedShape::new(shape),
@@ -189,9 +472,19 @@ pub fn scale_shape(shape: &SharedShape, shape_info: ShapeInfo) -> SharedShape {
                 }
             }
         }
+        ShapeType::TriMesh => {
+            if let Some(new_shape) = shape.as_trimesh() {
+                return SharedShape::new(new_shape.clone().scaled(&scale));
+            }
+        }
+        ShapeType::HeightField => {
+            if let Some(new_shape) = shape.as_heightfield() {
+                return SharedShape::new(new_shape.clone().scaled(&scale));
+            }
+        }
         ShapeType::Capsule => {
             if let Some(new_shape) = shape.as_capsule() {
-                if let Some(new_shape) = new_shape.scaled(&scale, SUBDIVISIONS) {
+                if let Some(new_shape) = new_shape.scaled(&scale, subdivisions) {
                     match new_shape {
                         Left(shape) => return SharedShape::new(shape),
                         Right(shape) => return SharedShape::new(shape),
@@ -199,19 +492,133 @@ pub fn scale_shape(shape: &SharedShape, shape_info: ShapeInfo) -> SharedShape {
                 }
             }
         }
+        ShapeType::Triangle => {
+            if let Some(new_shape) = shape.as_triangle() {
+                return SharedShape::triangle(
+                    new_shape.a.coords.component_mul(&scale).into(),
+                    new_shape.b.coords.component_mul(&scale).into(),
+                    new_shape.c.coords.component_mul(&scale).into(),
+                );
+            }
+        }
+        ShapeType::RoundTriangle => {
+            if let Some(new_shape) = shape.as_round_triangle() {
+                let inner_shape = new_shape.inner_shape;
+                return SharedShape::round_triangle(
+                    inner_shape.a.coords.component_mul(&scale).into(),
+                    inner_shape.b.coords.component_mul(&scale).into(),
+                    inner_shape.c.coords.component_mul(&scale).into(),
+                    new_shape.border_radius,
+                );
+            }
+        }
+        ShapeType::RoundCuboid => {
+            if let Some(new_shape) = shape.as_round_cuboid() {
+                return SharedShape::new(RoundShape {
+                    inner_shape: new_shape.inner_shape.scaled(&scale),
+                    border_radius: new_shape.border_radius,
+                });
+            }
+        }
+        ShapeType::RoundConvexPolyhedron => {
+            if let Some(new_shape) = shape.as_round_convex_polyhedron() {
+                if let Some(inner_shape) = new_shape.inner_shape.clone().scaled(&scale) {
+                    return SharedShape::new(RoundShape {
+                        inner_shape,
+                        border_radius: new_shape.border_radius,
+                    });
+                }
+            }
+        }
+        ShapeType::Segment => {
+            if let Some(new_shape) = shape.as_segment() {
+                return SharedShape::segment(
+                    new_shape.a.coords.component_mul(&scale).into(),
+                    new_shape.b.coords.component_mul(&scale).into(),
+                );
+            }
+        }
+        ShapeType::Cone => {
+            if let Some(new_shape) = shape.as_cone() {
+                if let Some(new_shape) = new_shape.scaled(&scale, subdivisions) {
+                    match new_shape {
+                        Left(shape) => return SharedShape::new(shape),
+                        Right(shape) => return SharedShape::new(shape),
+                    }
+                }
+            }
+        }
+        ShapeType::Cylinder => {
+            if let Some(new_shape) = shape.as_cylinder() {
+                if let Some(new_shape) = new_shape.scaled(&scale, subdivisions) {
+                    match new_shape {
+                        Left(shape) => return SharedShape::new(shape),
+                        Right(shape) => return SharedShape::new(shape),
+                    }
+                }
+            }
+        }
+        // The border radius can't be scaled non-uniformly, so only the inner shape is scaled
+        ShapeType::RoundCylinder => {
+            if let Some(new_shape) = shape.as_round_cylinder() {
+                let border_radius = new_shape.border_radius;
+                if let Some(inner_shape) = new_shape.inner_shape.scaled(&scale, subdivisions) {
+                    match inner_shape {
+                        Left(inner_shape) => {
+                            return SharedShape::new(RoundShape {
+                                inner_shape,
+                                border_radius,
+                            })
+                        }
+                        Right(inner_shape) => {
+                            return SharedShape::new(RoundShape {
+                                inner_shape,
+                                border_radius,
+                            })
+                        }
+                    }
+                }
+            }
+        }
+        ShapeType::RoundCone => {
+            if let Some(new_shape) = shape.as_round_cone() {
+                let border_radius = new_shape.border_radius;
+                if let Some(inner_shape) = new_shape.inner_shape.scaled(&scale, subdivisions) {
+                    match inner_shape {
+                        Left(inner_shape) => {
+                            return SharedShape::new(RoundShape {
+                                inner_shape,
+                                border_radius,
+                            })
+                        }
+                        Right(inner_shape) => {
+                            return SharedShape::new(RoundShape {
+                                inner_shape,
+                                border_radius,
+                            })
+                        }
+                    }
+                }
+            }
+        }
         ShapeType::Compound => {
             if let Some(new_shape) = shape.as_compound() {
                 let new_shapes = new_shape.shapes();
                 let mut shapes_vec = Vec::<(Isometry<Real>, SharedShape)>::new();
-                for shape in new_shapes {
-                    let new_shape = scale_shape(&shape.1, shape_info);
-                    shapes_vec.push((shape.0, new_shape));
+                for (position, sub_shape) in new_shapes {
+                    let (mut new_position, sub_shape) =
+                        unrotate_sub_shape(position, sub_shape, subdivisions);
+                    // Sub shape positions are scaled too, eg. the offset of halfspaces
+                    new_position.translation.vector =
+                        new_position.translation.vector.component_mul(&scale);
+                    let new_shape = scale_shape(&sub_shape, shape_info, subdivisions);
+                    shapes_vec.push((new_position, new_shape));
                 }
                 return SharedShape::compound(shapes_vec);
             }
         }
         _ => {
-            godot_error!("Shape type not supported for scaling");
+            physics_error!("Shape type not supported for scaling");
         }
     }
     shape.clone()
@@ -219,22 +626,62 @@ pub fn scale_shape(shape: &SharedShape, shape_info: ShapeInfo) -> SharedShape {
 pub struct Material {
     pub friction: Real,
     pub restitution: Real,
+    pub friction_combine_rule: Option<CoefficientCombineRule>,
+    pub restitution_combine_rule: Option<CoefficientCombineRule>,
     pub contact_skin: Real,
     pub collision_mask: u32,
     pub collision_layer: u32,
+    pub solver_membership: Option<u32>,
+    pub solver_filter: Option<u32>,
 }
 impl Material {
     pub fn new(collision_layer: u32, collision_mask: u32) -> Material {
         Material {
             friction: -1.0,
             restitution: -1.0,
+            friction_combine_rule: None,
+            restitution_combine_rule: None,
             contact_skin: -1.0,
             collision_layer,
             collision_mask,
+            solver_membership: None,
+            solver_filter: None,
         }
     }
 }
-fn shape_is_halfspace(shape: &SharedShape) -> bool {
+// Same order as the rapier combine rules
+pub fn combine_rule_from_i32(rule: i32) -> Option<CoefficientCombineRule> {
+    match rule {
+        0 => Some(CoefficientCombineRule::Average),
+        1 => Some(CoefficientCombineRule::Min),
+        2 => Some(CoefficientCombineRule::Multiply),
+        3 => Some(CoefficientCombineRule::Max),
+        _ => None,
+    }
+}
+// Godot marks rough materials with a negative friction and absorbent ones with a negative bounce.
+// When two rules differ rapier uses the one later in its order, so these win over the defaults.
+pub fn friction_combine_rule(
+    friction: Real,
+    rule: Option<CoefficientCombineRule>,
+) -> CoefficientCombineRule {
+    match rule {
+        Some(rule) => rule,
+        None if friction < 0.0 => CoefficientCombineRule::Max,
+        None => CoefficientCombineRule::Multiply,
+    }
+}
+pub fn restitution_combine_rule(
+    bounce: Real,
+    rule: Option<CoefficientCombineRule>,
+) -> CoefficientCombineRule {
+    match rule {
+        Some(rule) => rule,
+        None if bounce < 0.0 => CoefficientCombineRule::Min,
+        None => CoefficientCombineRule::Average,
+    }
+}
+pub fn shape_is_halfspace(shape: &SharedShape) -> bool {
     if shape.shape_type() == ShapeType::Compound {
         if let Some(shape) = shape.as_compound() {
             for shape in shape.shapes() {
@@ -267,16 +714,22 @@ impl PhysicsEngine {
             if mat.restitution >= 0.0 {
                 collider.set_restitution(mat.restitution);
             }
-            collider.set_friction_combine_rule(CoefficientCombineRule::Multiply);
-            collider.set_restitution_combine_rule(CoefficientCombineRule::Max);
+            collider.set_friction_combine_rule(
+                mat.friction_combine_rule
+                    .unwrap_or(CoefficientCombineRule::Multiply),
+            );
+            collider.set_restitution_combine_rule(
+                mat.restitution_combine_rule
+                    .unwrap_or(CoefficientCombineRule::Average),
+            );
             collider.set_density(1.0);
             collider.set_collision_groups(InteractionGroups {
                 memberships: Group::from(mat.collision_layer),
                 filter: Group::from(mat.collision_mask),
             });
             collider.set_solver_groups(InteractionGroups {
-                memberships: Group::GROUP_1,
-                filter: Group::GROUP_1,
+                memberships: mat.solver_membership.map_or(Group::GROUP_1, Group::from),
+                filter: mat.solver_filter.map_or(Group::GROUP_1, Group::from),
             });
             collider.set_contact_skin(mat.contact_skin);
             collider.set_contact_force_event_threshold(-Real::MAX);
@@ -373,7 +826,7 @@ impl PhysicsEngine {
         shape_info: ShapeInfo,
     ) {
         if let Some(shape) = self.get_shape(shape_info.handle) {
-            let new_shape = scale_shape(shape, shape_info);
+            let new_shape = scale_shape(shape, shape_info, self.shape_subdivisions);
             if let Some(physics_world) = self.get_mut_world(world_handle) {
                 if let Some(collider) = physics_world
                     .physics_objects
@@ -410,3 +863,66 @@ impl PhysicsEngine {
         }
     }
 }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn combine_rule_from_i32_matches_rapier_order() {
+        for rule in 0..4 {
+            assert_eq!(
+                combine_rule_from_i32(rule).map(|combine_rule| combine_rule as i32),
+                Some(rule)
+            );
+        }
+        assert_eq!(combine_rule_from_i32(1), Some(CoefficientCombineRule::Min));
+        assert_eq!(combine_rule_from_i32(3), Some(CoefficientCombineRule::Max));
+    }
+    #[test]
+    fn combine_rule_from_i32_out_of_range_is_default() {
+        assert_eq!(combine_rule_from_i32(-1), None);
+        assert_eq!(combine_rule_from_i32(4), None);
+    }
+    #[test]
+    fn absorbent_restitution_wins_against_default() {
+        let mut bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+        let mut touching_ball = |x: Real, bounce: Real| {
+            let body_handle = bodies.insert(
+                RigidBodyBuilder::dynamic()
+                    .translation(Vector::x() * x)
+                    .build(),
+            );
+            colliders.insert_with_parent(
+                ColliderBuilder::ball(1.0)
+                    .restitution(bounce.abs())
+                    .restitution_combine_rule(restitution_combine_rule(bounce, None))
+                    .build(),
+                body_handle,
+                &mut bodies,
+            )
+        };
+        let default_ball = touching_ball(0.0, 0.8);
+        let absorbent_ball = touching_ball(1.5, -0.2);
+        let mut narrow_phase = NarrowPhase::new();
+        PhysicsPipeline::new().step(
+            &Vector::zeros(),
+            &IntegrationParameters::default(),
+            &mut IslandManager::new(),
+            &mut DefaultBroadPhase::new(),
+            &mut narrow_phase,
+            &mut bodies,
+            &mut colliders,
+            &mut ImpulseJointSet::new(),
+            &mut MultibodyJointSet::new(),
+            &mut CCDSolver::new(),
+            None,
+            &(),
+            &(),
+        );
+        let contact_pair = narrow_phase
+            .contact_pair(default_ball, absorbent_ball)
+            .unwrap();
+        let solver_contact = &contact_pair.manifolds[0].data.solver_contacts[0];
+        assert_eq!(solver_contact.restitution, 0.2);
+    }
+}
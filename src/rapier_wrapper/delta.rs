@@ -0,0 +1,279 @@
+use hashbrown::HashMap;
+use hashbrown::HashSet;
+use rapier::prelude::*;
+
+use crate::rapier_wrapper::prelude::*;
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct BodyDeltaState {
+    pub position: Isometry<Real>,
+    pub vels: RigidBodyVelocity,
+    pub sleeping: bool,
+}
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct ColliderDeltaState {
+    // Relative to the parent if the collider has one
+    pub position: Isometry<Real>,
+    pub enabled: bool,
+}
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct JointDeltaState {
+    pub data: GenericJoint,
+    pub impulses: SpatialVector<Real>,
+}
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct WorldDelta {
+    pub baseline_id: u64,
+    pub id: u64,
+    pub bodies: Vec<(RigidBodyHandle, BodyDeltaState)>,
+    pub colliders: Vec<(ColliderHandle, ColliderDeltaState)>,
+    pub joints: Vec<(ImpulseJointHandle, JointDeltaState)>,
+    // Objects that are new or were removed since the baseline. New objects also have their state above.
+    pub added_bodies: Vec<RigidBodyHandle>,
+    pub added_colliders: Vec<ColliderHandle>,
+    pub added_joints: Vec<ImpulseJointHandle>,
+    pub removed_bodies: Vec<RigidBodyHandle>,
+    pub removed_colliders: Vec<ColliderHandle>,
+    pub removed_joints: Vec<ImpulseJointHandle>,
+}
+#[derive(Default)]
+pub struct DeltaBaseline {
+    id: u64,
+    bodies: HashMap<RigidBodyHandle, BodyDeltaState>,
+    colliders: HashMap<ColliderHandle, ColliderDeltaState>,
+    joints: HashMap<ImpulseJointHandle, JointDeltaState>,
+}
+// Each consumer acknowledges baselines on its own, so they are kept separately
+#[derive(Default)]
+pub struct DeltaBaselines {
+    consumers: HashMap<u64, Vec<DeltaBaseline>>,
+    last_id: u64,
+}
+// Baselines kept per consumer while waiting for an acknowledgement
+const MAX_DELTA_BASELINES: usize = 32;
+fn body_delta_state(body: &RigidBody) -> BodyDeltaState {
+    BodyDeltaState {
+        position: *body.position(),
+        vels: *body.vels(),
+        sleeping: body.is_sleeping(),
+    }
+}
+fn collider_delta_state(collider: &Collider) -> ColliderDeltaState {
+    let position = match collider.position_wrt_parent() {
+        Some(position) => *position,
+        None => *collider.position(),
+    };
+    ColliderDeltaState {
+        position,
+        enabled: collider.is_enabled(),
+    }
+}
+impl PhysicsEngine {
+    // Stores the current state as a new baseline of the consumer and returns what changed since
+    // the given one. An unknown baseline results in a delta containing everything.
+    pub fn world_export_delta(
+        &mut self,
+        world_handle: WorldHandle,
+        consumer_id: u64,
+        baseline_id: u64,
+    ) -> Option<WorldDelta> {
+        let Some(physics_world) = self.get_mut_world(world_handle) else {
+            return None;
+        };
+        let physics_objects = &physics_world.physics_objects;
+        let delta_baselines = &mut physics_world.delta_baselines;
+        delta_baselines.last_id += 1;
+        let new_id = delta_baselines.last_id;
+        let baselines = delta_baselines.consumers.entry(consumer_id).or_default();
+        // Baselines older than the acknowledged one won't be used anymore
+        if baselines.iter().any(|baseline| baseline.id == baseline_id) {
+            baselines.retain(|baseline| baseline.id >= baseline_id);
+        }
+        let mut new_baseline = DeltaBaseline {
+            id: new_id,
+            ..Default::default()
+        };
+        let empty_baseline = DeltaBaseline::default();
+        let baseline = baselines
+            .iter()
+            .find(|baseline| baseline.id == baseline_id)
+            .unwrap_or(&empty_baseline);
+        let mut delta = WorldDelta {
+            baseline_id: baseline.id,
+            id: new_id,
+            bodies: Vec::new(),
+            colliders: Vec::new(),
+            joints: Vec::new(),
+            added_bodies: Vec::new(),
+            added_colliders: Vec::new(),
+            added_joints: Vec::new(),
+            removed_bodies: Vec::new(),
+            removed_colliders: Vec::new(),
+            removed_joints: Vec::new(),
+        };
+        let island_manager = &physics_objects.island_manager;
+        let mut active_bodies = HashSet::default();
+        active_bodies.extend(island_manager.active_dynamic_bodies());
+        active_bodies.extend(island_manager.active_kinematic_bodies());
+        for (handle, body) in physics_objects.rigid_body_set.iter() {
+            let state = body_delta_state(body);
+            let baseline_state = baseline.bodies.get(&handle);
+            if baseline_state.is_none() {
+                delta.added_bodies.push(handle);
+            }
+            // Active bodies are moving, so they always changed
+            if active_bodies.contains(&handle) || baseline_state != Some(&state) {
+                delta.bodies.push((handle, state));
+            }
+            new_baseline.bodies.insert(handle, state);
+        }
+        for (handle, collider) in physics_objects.collider_set.iter() {
+            let state = collider_delta_state(collider);
+            let baseline_state = baseline.colliders.get(&handle);
+            if baseline_state.is_none() {
+                delta.added_colliders.push(handle);
+            }
+            if baseline_state != Some(&state) {
+                delta.colliders.push((handle, state));
+            }
+            new_baseline.colliders.insert(handle, state);
+        }
+        for (handle, joint) in physics_objects.impulse_joint_set.iter() {
+            let state = JointDeltaState {
+                data: joint.data,
+                impulses: joint.impulses,
+            };
+            let baseline_state = baseline.joints.get(&handle);
+            if baseline_state.is_none() {
+                delta.added_joints.push(handle);
+            }
+            if baseline_state != Some(&state) {
+                delta.joints.push((handle, state));
+            }
+            new_baseline.joints.insert(handle, state);
+        }
+        delta.removed_bodies.extend(
+            baseline
+                .bodies
+                .keys()
+                .filter(|handle| !new_baseline.bodies.contains_key(*handle)),
+        );
+        delta.removed_colliders.extend(
+            baseline
+                .colliders
+                .keys()
+                .filter(|handle| !new_baseline.colliders.contains_key(*handle)),
+        );
+        delta.removed_joints.extend(
+            baseline
+                .joints
+                .keys()
+                .filter(|handle| !new_baseline.joints.contains_key(*handle)),
+        );
+        baselines.push(new_baseline);
+        if baselines.len() > MAX_DELTA_BASELINES {
+            let excess = baselines.len() - MAX_DELTA_BASELINES;
+            baselines.drain(..excess);
+        }
+        Some(delta)
+    }
+
+    pub fn world_remove_delta_consumer(&mut self, world_handle: WorldHandle, consumer_id: u64) {
+        if let Some(physics_world) = self.get_mut_world(world_handle) {
+            physics_world.delta_baselines.consumers.remove(&consumer_id);
+        }
+    }
+
+    // Deltas only carry state, objects are matched by handle and are never created or removed
+    // here, as they are owned by the server objects. A delta with objects added that don't exist in
+    // this world, or objects removed that still exist, is not applied. Returns the number of these
+    // objects.
+    pub fn world_apply_delta(&mut self, world_handle: WorldHandle, delta: &WorldDelta) -> usize {
+        let Some(physics_world) = self.get_mut_world(world_handle) else {
+            return 0;
+        };
+        let physics_objects = &mut physics_world.physics_objects;
+        let missing_bodies = delta
+            .added_bodies
+            .iter()
+            .filter(|handle| !physics_objects.rigid_body_set.contains(**handle))
+            .count();
+        let missing_colliders = delta
+            .added_colliders
+            .iter()
+            .filter(|handle| !physics_objects.collider_set.contains(**handle))
+            .count();
+        let missing_joints = delta
+            .added_joints
+            .iter()
+            .filter(|handle| !physics_objects.impulse_joint_set.contains(**handle))
+            .count();
+        let remaining_bodies = delta
+            .removed_bodies
+            .iter()
+            .filter(|handle| physics_objects.rigid_body_set.contains(**handle))
+            .count();
+        let remaining_colliders = delta
+            .removed_colliders
+            .iter()
+            .filter(|handle| physics_objects.collider_set.contains(**handle))
+            .count();
+        let remaining_joints = delta
+            .removed_joints
+            .iter()
+            .filter(|handle| physics_objects.impulse_joint_set.contains(**handle))
+            .count();
+        let structural_changes = missing_bodies
+            + missing_colliders
+            + missing_joints
+            + remaining_bodies
+            + remaining_colliders
+            + remaining_joints;
+        if structural_changes > 0 {
+            return structural_changes;
+        }
+        for (handle, state) in &delta.bodies {
+            if let Some(body) = physics_objects.rigid_body_set.get_mut(*handle) {
+                body.set_position(state.position, false);
+                body.set_linvel(state.vels.linvel, false);
+                body.set_angvel(state.vels.angvel, false);
+                if state.sleeping {
+                    body.sleep();
+                } else {
+                    body.wake_up(true);
+                }
+            }
+        }
+        for (handle, state) in &delta.colliders {
+            if let Some(collider) = physics_objects.collider_set.get_mut(*handle) {
+                if collider.parent().is_some() {
+                    collider.set_position_wrt_parent(state.position);
+                } else {
+                    collider.set_position(state.position);
+                }
+                collider.set_enabled(state.enabled);
+            }
+        }
+        for (handle, state) in &delta.joints {
+            if let Some(joint) = physics_objects.impulse_joint_set.get_mut(*handle) {
+                joint.data = state.data;
+                joint.impulses = state.impulses;
+            }
+        }
+        0
+    }
+}
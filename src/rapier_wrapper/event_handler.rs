@@ -1,7 +1,8 @@
 use crossbeam::channel::Sender;
-use godot::log::godot_error;
 use rapier::crossbeam;
 use rapier::prelude::*;
+
+use crate::rapier_wrapper::logging::physics_error;
 pub struct ContactEventHandler {
     collision_send: Sender<CollisionEvent>,
     contact_force_send: Sender<(ContactForceEvent, ContactPair)>,
@@ -28,7 +29,7 @@ impl EventHandler for ContactEventHandler {
         match self.collision_send.send(event) {
             Ok(_) => (),
             Err(err) => {
-                godot_error!("Failed to send collision event {}", err.to_string());
+                physics_error!("Failed to send collision event {}", err.to_string());
             }
         }
     }
@@ -45,7 +46,7 @@ impl EventHandler for ContactEventHandler {
         match self.contact_force_send.send((result, contact_pair.clone())) {
             Ok(_) => (),
             Err(err) => {
-                godot_error!("Failed to send contact force event {}", err.to_string());
+                physics_error!("Failed to send contact force event {}", err.to_string());
             }
         }
     }
@@ -1,3 +1,4 @@
+use hashbrown::HashMap;
 use rapier::prelude::*;
 use salva::math::Vector as SalvaVector;
 use salva::object::*;
@@ -6,6 +7,17 @@ use salva::solver::*;
 use super::shape::point_array_to_vec;
 use crate::rapier_wrapper::prelude::*;
 use crate::types::PackedVectorArray;
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct FluidExport {
+    pub handle: HandleDouble,
+    pub density: Real,
+    pub positions: Vec<Point<Real>>,
+    pub velocities: Vec<Vector<Real>>,
+    pub accelerations: Vec<Vector<Real>>,
+}
 impl PhysicsEngine {
     pub fn fluid_create(&mut self, world_handle: WorldHandle, density: Real) -> HandleDouble {
         if let Some(physics_world) = self.get_mut_world(world_handle) {
@@ -409,6 +421,88 @@ impl PhysicsEngine {
         }
     }
 
+    pub fn world_export_fluids(&self, world_handle: WorldHandle) -> Vec<FluidExport> {
+        let mut fluids = Vec::new();
+        if let Some(physics_world) = self.get_world(world_handle) {
+            for (fluid_handle, fluid) in physics_world.fluids_pipeline.liquid_world.fluids().iter()
+            {
+                fluids.push(FluidExport {
+                    handle: fluid_handle_to_handle(fluid_handle),
+                    density: fluid.density0,
+                    positions: fluid.positions.clone(),
+                    velocities: fluid.velocities.clone(),
+                    accelerations: fluid.accelerations.clone(),
+                });
+            }
+        }
+        fluids
+    }
+
+    // Returns the mapping from the exported fluid handles to the ones in the world.
+    // Fluids can't be removed yet, so existing ones are reused or emptied.
+    pub fn world_import_fluids(
+        &mut self,
+        world_handle: WorldHandle,
+        fluids: Vec<FluidExport>,
+    ) -> HashMap<HandleDouble, HandleDouble> {
+        let mut fluid_handles = HashMap::default();
+        let Some(physics_world) = self.get_mut_world(world_handle) else {
+            return fluid_handles;
+        };
+        let liquid_world = &mut physics_world.fluids_pipeline.liquid_world;
+        let particle_radius = liquid_world.particle_radius();
+        let mut free_handles: Vec<FluidHandle> = liquid_world
+            .fluids()
+            .iter()
+            .map(|(handle, _)| handle)
+            .collect();
+        for fluid_handle in &free_handles {
+            let Some(fluid) = liquid_world.fluids_mut().get_mut(*fluid_handle) else {
+                continue;
+            };
+            fluid.positions.clear();
+            fluid.velocities.clear();
+            fluid.accelerations.clear();
+            fluid.volumes.clear();
+            fluid.nonpressure_forces.clear();
+        }
+        for fluid_export in fluids {
+            let mut imported_fluid = Fluid::new(
+                fluid_export.positions,
+                particle_radius,
+                fluid_export.density,
+            );
+            let point_count = imported_fluid.positions.len();
+            if fluid_export.velocities.len() == point_count {
+                imported_fluid.velocities = fluid_export.velocities;
+            }
+            if fluid_export.accelerations.len() == point_count {
+                imported_fluid.accelerations = fluid_export.accelerations;
+            }
+            let requested_handle = handle_to_fluid_handle(fluid_export.handle);
+            let free_index = free_handles
+                .iter()
+                .position(|handle| *handle == requested_handle)
+                .or(if free_handles.is_empty() {
+                    None
+                } else {
+                    Some(0)
+                });
+            let fluid_handle = match free_index {
+                Some(free_index) => {
+                    let fluid_handle = free_handles.remove(free_index);
+                    if let Some(fluid) = liquid_world.fluids_mut().get_mut(fluid_handle) {
+                        *fluid = imported_fluid;
+                    }
+                    fluid_handle
+                }
+                None => liquid_world.add_fluid(imported_fluid),
+            };
+            fluid_handles.insert(fluid_export.handle, fluid_handle_to_handle(fluid_handle));
+        }
+        fluid_handles
+    }
+
     pub fn fluid_destroy(&mut self, world_handle: WorldHandle, fluid_handle: HandleDouble) {
         if let Some(physics_world) = self.get_mut_world(world_handle)
             && let Some(_fluid) = physics_world
@@ -0,0 +1,294 @@
+use rapier::prelude::*;
+
+use crate::rapier_wrapper::prelude::*;
+// Receives the content of a shape or state, one word at a time
+trait ContentWriter {
+    fn write_u64(&mut self, value: u64);
+    fn write_bool(&mut self, value: bool) {
+        self.write_u64(value as u64);
+    }
+    fn write_real(&mut self, value: Real) {
+        self.write_u64(value.to_bits() as u64);
+    }
+    fn write_reals<'a>(&mut self, values: impl Iterator<Item = &'a Real>) {
+        for value in values {
+            self.write_real(*value);
+        }
+    }
+}
+// FNV-1a, so that the hash is the same on every platform and rust version
+struct StateHasher {
+    hash: u64,
+}
+impl StateHasher {
+    fn new() -> Self {
+        Self {
+            hash: 0xcbf29ce484222325,
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+impl ContentWriter for StateHasher {
+    fn write_u64(&mut self, value: u64) {
+        for byte in value.to_le_bytes() {
+            self.hash ^= byte as u64;
+            self.hash = self.hash.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+// Keeps the content itself, to tell apart shapes whose hashes collide
+impl ContentWriter for Vec<u64> {
+    fn write_u64(&mut self, value: u64) {
+        self.push(value);
+    }
+}
+fn write_points(hasher: &mut impl ContentWriter, points: &[Point<Real>]) {
+    hasher.write_u64(points.len() as u64);
+    for point in points {
+        hasher.write_reals(point.coords.iter());
+    }
+}
+fn write_indices<const N: usize>(hasher: &mut impl ContentWriter, indices: &[[u32; N]]) {
+    hasher.write_u64(indices.len() as u64);
+    for index in indices.iter().flatten() {
+        hasher.write_u64(*index as u64);
+    }
+}
+// Returns false for shape types that can't be hashed, which are then never shared
+fn write_shape(hasher: &mut impl ContentWriter, shape: &dyn Shape) -> bool {
+    hasher.write_u64(shape.shape_type() as u64);
+    if let Some(ball) = shape.as_ball() {
+        hasher.write_real(ball.radius);
+    } else if let Some(cuboid) = shape.as_cuboid() {
+        hasher.write_reals(cuboid.half_extents.iter());
+    } else if let Some(capsule) = shape.as_capsule() {
+        write_points(hasher, &[capsule.segment.a, capsule.segment.b]);
+        hasher.write_real(capsule.radius);
+    } else if let Some(segment) = shape.as_segment() {
+        write_points(hasher, &[segment.a, segment.b]);
+    } else if let Some(triangle) = shape.as_triangle() {
+        write_points(hasher, triangle.vertices());
+    } else if let Some(halfspace) = shape.as_halfspace() {
+        hasher.write_reals(halfspace.normal.iter());
+    } else if let Some(polyline) = shape.as_polyline() {
+        write_points(hasher, polyline.vertices());
+        write_indices(hasher, polyline.indices());
+    } else if let Some(compound) = shape.as_compound() {
+        hasher.write_u64(compound.shapes().len() as u64);
+        for (position, sub_shape) in compound.shapes() {
+            hasher.write_reals(position.to_homogeneous().iter());
+            if !write_shape(hasher, sub_shape.as_ref()) {
+                return false;
+            }
+        }
+    } else {
+        return write_dim_shape(hasher, shape);
+    }
+    true
+}
+#[cfg(feature = "dim2")]
+fn write_dim_shape(hasher: &mut impl ContentWriter, shape: &dyn Shape) -> bool {
+    if let Some(convex_polygon) = shape.as_convex_polygon() {
+        write_points(hasher, convex_polygon.points());
+    } else if let Some(round_cuboid) = shape.as_round_cuboid() {
+        hasher.write_real(round_cuboid.border_radius);
+        return write_shape(hasher, &round_cuboid.inner_shape);
+    } else if let Some(round_triangle) = shape.as_round_triangle() {
+        hasher.write_real(round_triangle.border_radius);
+        return write_shape(hasher, &round_triangle.inner_shape);
+    } else if let Some(round_convex_polygon) = shape.as_round_convex_polygon() {
+        hasher.write_real(round_convex_polygon.border_radius);
+        return write_shape(hasher, &round_convex_polygon.inner_shape);
+    } else {
+        return false;
+    }
+    true
+}
+#[cfg(feature = "dim3")]
+fn write_dim_shape(hasher: &mut impl ContentWriter, shape: &dyn Shape) -> bool {
+    if let Some(convex_polyhedron) = shape.as_convex_polyhedron() {
+        write_points(hasher, convex_polyhedron.points());
+    } else if let Some(trimesh) = shape.as_trimesh() {
+        write_points(hasher, trimesh.vertices());
+        write_indices(hasher, trimesh.indices());
+        hasher.write_u64(trimesh.flags().bits() as u64);
+    } else if let Some(cylinder) = shape.as_cylinder() {
+        hasher.write_real(cylinder.half_height);
+        hasher.write_real(cylinder.radius);
+    } else if let Some(cone) = shape.as_cone() {
+        hasher.write_real(cone.half_height);
+        hasher.write_real(cone.radius);
+    } else if let Some(round_cuboid) = shape.as_round_cuboid() {
+        hasher.write_real(round_cuboid.border_radius);
+        return write_shape(hasher, &round_cuboid.inner_shape);
+    } else if let Some(round_triangle) = shape.as_round_triangle() {
+        hasher.write_real(round_triangle.border_radius);
+        return write_shape(hasher, &round_triangle.inner_shape);
+    } else if let Some(round_cylinder) = shape.as_round_cylinder() {
+        hasher.write_real(round_cylinder.border_radius);
+        return write_shape(hasher, &round_cylinder.inner_shape);
+    } else if let Some(round_cone) = shape.as_round_cone() {
+        hasher.write_real(round_cone.border_radius);
+        return write_shape(hasher, &round_cone.inner_shape);
+    } else if let Some(round_convex_polyhedron) = shape.as_round_convex_polyhedron() {
+        hasher.write_real(round_convex_polyhedron.border_radius);
+        return write_shape(hasher, &round_convex_polyhedron.inner_shape);
+    } else {
+        return false;
+    }
+    true
+}
+// Hash of the shape geometry, used to share identical shapes
+pub fn shape_content_hash(shape: &dyn Shape) -> Option<u64> {
+    let mut hasher = StateHasher::new();
+    if !write_shape(&mut hasher, shape) {
+        return None;
+    }
+    Some(hasher.finish())
+}
+// Shapes that can't be hashed are never equal, same as they are never shared
+pub fn shape_content_eq(shape: &dyn Shape, other: &dyn Shape) -> bool {
+    let mut content = Vec::new();
+    let mut other_content = Vec::new();
+    write_shape(&mut content, shape)
+        && write_shape(&mut other_content, other)
+        && content == other_content
+}
+fn rigid_body_hash(body: &RigidBody) -> u64 {
+    let mut hasher = StateHasher::new();
+    hasher.write_reals(body.position().to_homogeneous().iter());
+    hasher.write_reals(body.linvel().iter());
+    #[cfg(feature = "dim2")]
+    hasher.write_real(body.angvel());
+    #[cfg(feature = "dim3")]
+    hasher.write_reals(body.angvel().iter());
+    hasher.write_bool(body.is_sleeping());
+    hasher.finish()
+}
+fn joint_hash(joint: &ImpulseJoint) -> u64 {
+    let mut hasher = StateHasher::new();
+    hasher.write_reals(joint.data.local_frame1.to_homogeneous().iter());
+    hasher.write_reals(joint.data.local_frame2.to_homogeneous().iter());
+    hasher.write_reals(joint.impulses.iter());
+    hasher.write_bool(joint.data.is_enabled());
+    hasher.finish()
+}
+impl PhysicsEngine {
+    // Hashes are summed so the result doesn't depend on the iteration order
+    pub fn world_get_state_hash(&self, world_handle: WorldHandle) -> u64 {
+        let mut hasher = StateHasher::new();
+        if let Some(physics_world) = self.get_world(world_handle) {
+            let physics_objects = &physics_world.physics_objects;
+            let mut bodies_hash: u64 = 0;
+            for (_, body) in physics_objects.rigid_body_set.iter() {
+                bodies_hash = bodies_hash.wrapping_add(rigid_body_hash(body));
+            }
+            let mut joints_hash: u64 = 0;
+            for (_, joint) in physics_objects.impulse_joint_set.iter() {
+                joints_hash = joints_hash.wrapping_add(joint_hash(joint));
+            }
+            hasher.write_u64(physics_objects.rigid_body_set.len() as u64);
+            hasher.write_u64(bodies_hash);
+            hasher.write_u64(physics_objects.impulse_joint_set.len() as u64);
+            hasher.write_u64(joints_hash);
+        }
+        hasher.finish()
+    }
+
+    pub fn body_get_state_hash(
+        &self,
+        world_handle: WorldHandle,
+        body_handle: RigidBodyHandle,
+    ) -> u64 {
+        if let Some(physics_world) = self.get_world(world_handle)
+            && let Some(body) = physics_world
+                .physics_objects
+                .rigid_body_set
+                .get(body_handle)
+        {
+            return rigid_body_hash(body);
+        }
+        0
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "dim2")]
+    fn heightfield() -> SharedShape {
+        SharedShape::heightfield(
+            nalgebra::DVector::from_vec(vec![0.0, 1.0, 0.0]),
+            Vector::repeat(1.0),
+        )
+    }
+    #[cfg(feature = "dim3")]
+    fn heightfield() -> SharedShape {
+        SharedShape::heightfield(nalgebra::DMatrix::zeros(2, 2), Vector::repeat(1.0))
+    }
+    fn ball_and_cuboid(offset: Real) -> SharedShape {
+        SharedShape::compound(vec![
+            (Isometry::identity(), SharedShape::ball(1.0)),
+            (
+                Isometry::from_parts(
+                    Translation::from(Vector::x() * offset),
+                    Rotation::identity(),
+                ),
+                SharedShape::new(Cuboid::new(Vector::repeat(0.5))),
+            ),
+        ])
+    }
+    #[test]
+    fn identical_shapes_have_the_same_content() {
+        let ball = SharedShape::ball(1.0);
+        let other_ball = SharedShape::ball(1.0);
+        assert!(shape_content_hash(ball.as_ref()).is_some());
+        assert_eq!(
+            shape_content_hash(ball.as_ref()),
+            shape_content_hash(other_ball.as_ref())
+        );
+        assert!(shape_content_eq(ball.as_ref(), other_ball.as_ref()));
+        let compound = ball_and_cuboid(2.0);
+        let other_compound = ball_and_cuboid(2.0);
+        assert_eq!(
+            shape_content_hash(compound.as_ref()),
+            shape_content_hash(other_compound.as_ref())
+        );
+        assert!(shape_content_eq(compound.as_ref(), other_compound.as_ref()));
+    }
+    #[test]
+    fn different_shapes_have_different_content() {
+        let ball = SharedShape::ball(1.0);
+        let bigger_ball = SharedShape::ball(2.0);
+        assert_ne!(
+            shape_content_hash(ball.as_ref()),
+            shape_content_hash(bigger_ball.as_ref())
+        );
+        assert!(!shape_content_eq(ball.as_ref(), bigger_ball.as_ref()));
+        let cuboid = SharedShape::new(Cuboid::new(Vector::repeat(1.0)));
+        assert!(!shape_content_eq(ball.as_ref(), cuboid.as_ref()));
+        let compound = ball_and_cuboid(2.0);
+        let moved_compound = ball_and_cuboid(3.0);
+        assert_ne!(
+            shape_content_hash(compound.as_ref()),
+            shape_content_hash(moved_compound.as_ref())
+        );
+        assert!(!shape_content_eq(
+            compound.as_ref(),
+            moved_compound.as_ref()
+        ));
+    }
+    #[test]
+    fn unsupported_shapes_are_never_equal() {
+        let heightfield = heightfield();
+        assert_eq!(shape_content_hash(heightfield.as_ref()), None);
+        assert!(!shape_content_eq(
+            heightfield.as_ref(),
+            heightfield.as_ref()
+        ));
+        let compound = SharedShape::compound(vec![(Isometry::identity(), heightfield)]);
+        assert_eq!(shape_content_hash(compound.as_ref()), None);
+    }
+}
@@ -0,0 +1,31 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+// Godot logging needs a running engine, so it is only used once the extension is loaded.
+// Otherwise (eg. in the replay binary) messages are written to stderr and stdout.
+static ENGINE_LOGGING: AtomicBool = AtomicBool::new(false);
+pub fn set_engine_logging(enabled: bool) {
+    ENGINE_LOGGING.store(enabled, Ordering::Relaxed);
+}
+pub fn engine_logging() -> bool {
+    ENGINE_LOGGING.load(Ordering::Relaxed)
+}
+macro_rules! physics_error {
+    ($($args:tt)*) => {
+        if $crate::rapier_wrapper::logging::engine_logging() {
+            godot::log::godot_error!($($args)*);
+        } else {
+            eprintln!($($args)*);
+        }
+    };
+}
+macro_rules! physics_print {
+    ($($args:tt)*) => {
+        if $crate::rapier_wrapper::logging::engine_logging() {
+            godot::log::godot_print!($($args)*);
+        } else {
+            println!($($args)*);
+        }
+    };
+}
+pub(crate) use physics_error;
+pub(crate) use physics_print;
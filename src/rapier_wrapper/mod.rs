@@ -1,10 +1,14 @@
 pub mod body;
+pub mod character;
 pub mod collider;
 pub mod convert;
+pub mod delta;
 pub mod event_handler;
 pub mod fluid;
 pub mod handle;
+pub mod hash;
 pub mod joint;
+pub mod logging;
 pub mod physics_hooks;
 pub mod physics_world;
 pub mod prelude;
@@ -1,12 +1,17 @@
 use std::num::NonZeroUsize;
 
-use godot::log::godot_print;
+use hashbrown::HashMap;
 use rapier::crossbeam;
 use rapier::data::Arena;
 use rapier::math::DEFAULT_EPSILON;
 use rapier::prelude::*;
+use salva::integrations::rapier::ColliderSampling;
 use salva::integrations::rapier::FluidsPipeline;
+use salva::object::Boundary;
 
+use crate::rapier_wrapper::hash::shape_content_eq;
+use crate::rapier_wrapper::hash::shape_content_hash;
+use crate::rapier_wrapper::logging::physics_print;
 use crate::rapier_wrapper::prelude::*;
 use crate::servers::rapier_physics_server_extra::PhysicsCollisionObjects;
 use crate::spaces::rapier_space::RapierSpace;
@@ -69,10 +74,29 @@ pub struct PhysicsObjects {
 
     pub handle: WorldHandle,
 }
+impl PhysicsObjects {
+    pub fn new(handle: WorldHandle) -> PhysicsObjects {
+        PhysicsObjects {
+            query_pipeline: QueryPipeline::new(),
+            island_manager: IslandManager::new(),
+            broad_phase: DefaultBroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            impulse_joint_set: ImpulseJointSet::new(),
+            multibody_joint_set: MultibodyJointSet::new(),
+            ccd_solver: CCDSolver::new(),
+
+            rigid_body_set: RigidBodySet::new(),
+            collider_set: ColliderSet::new(),
+
+            handle,
+        }
+    }
+}
 pub struct PhysicsWorld {
     pub physics_objects: PhysicsObjects,
     pub physics_pipeline: PhysicsPipeline,
     pub fluids_pipeline: FluidsPipeline,
+    pub delta_baselines: DeltaBaselines,
 }
 impl PhysicsWorld {
     pub fn new(settings: &WorldSettings) -> PhysicsWorld {
@@ -81,25 +105,13 @@ impl PhysicsWorld {
             physics_pipeline.counters.enable();
         }
         PhysicsWorld {
-            physics_objects: PhysicsObjects {
-                query_pipeline: QueryPipeline::new(),
-                island_manager: IslandManager::new(),
-                broad_phase: DefaultBroadPhase::new(),
-                narrow_phase: NarrowPhase::new(),
-                impulse_joint_set: ImpulseJointSet::new(),
-                multibody_joint_set: MultibodyJointSet::new(),
-                ccd_solver: CCDSolver::new(),
-
-                rigid_body_set: RigidBodySet::new(),
-                collider_set: ColliderSet::new(),
-
-                handle: WorldHandle::default(),
-            },
+            physics_objects: PhysicsObjects::new(WorldHandle::default()),
             physics_pipeline,
             fluids_pipeline: FluidsPipeline::new(
                 settings.particle_radius,
                 settings.smoothing_factor,
             ),
+            delta_baselines: DeltaBaselines::default(),
         }
     }
 
@@ -111,19 +123,6 @@ impl PhysicsWorld {
         space: &mut RapierSpace,
         physics_collision_objects: &mut PhysicsCollisionObjects,
     ) {
-        let mut integration_parameters = IntegrationParameters::default();
-        integration_parameters.length_unit = settings.length_unit;
-        integration_parameters.dt = settings.dt;
-        integration_parameters.max_ccd_substeps = settings.max_ccd_substeps;
-        if settings.num_solver_iterations > 0 {
-            integration_parameters.num_solver_iterations =
-                NonZeroUsize::new(settings.num_solver_iterations).unwrap();
-        }
-        integration_parameters.num_additional_friction_iterations =
-            settings.num_additional_friction_iterations;
-        integration_parameters.num_internal_pgs_iterations = settings.num_internal_pgs_iterations;
-        let gravity = settings.pixel_gravity;
-        let liquid_gravity = settings.pixel_liquid_gravity;
         let physics_hooks = PhysicsHooksCollisionFilter {
             collision_filter_body_callback: &collision_filter_body_callback,
             collision_modify_contacts_callback: &collision_modify_contacts_callback,
@@ -133,29 +132,7 @@ impl PhysicsWorld {
         let (collision_send, collision_recv) = crossbeam::channel::unbounded();
         let (contact_force_send, contact_force_recv) = crossbeam::channel::unbounded();
         let event_handler = ContactEventHandler::new(collision_send, contact_force_send);
-        self.physics_pipeline.step(
-            &gravity,
-            &integration_parameters,
-            &mut self.physics_objects.island_manager,
-            &mut self.physics_objects.broad_phase,
-            &mut self.physics_objects.narrow_phase,
-            &mut self.physics_objects.rigid_body_set,
-            &mut self.physics_objects.collider_set,
-            &mut self.physics_objects.impulse_joint_set,
-            &mut self.physics_objects.multibody_joint_set,
-            &mut self.physics_objects.ccd_solver,
-            Some(&mut self.physics_objects.query_pipeline),
-            &physics_hooks,
-            &event_handler,
-        );
-        if self.fluids_pipeline.liquid_world.fluids().len() > 0 {
-            self.fluids_pipeline.step(
-                &liquid_gravity,
-                integration_parameters.dt,
-                &self.physics_objects.collider_set,
-                &mut self.physics_objects.rigid_body_set,
-            );
-        }
+        self.step_pipelines(settings, &physics_hooks, &event_handler);
         for handle in self.physics_objects.island_manager.active_dynamic_bodies() {
             // Send the active body event.
             let active_body_info = ActiveBodyInfo {
@@ -265,6 +242,51 @@ impl PhysicsWorld {
         }
     }
 
+    // Steps only the rapier and salva pipelines, without sending any events back to the spaces
+    pub fn step_pipelines(
+        &mut self,
+        settings: &SimulationSettings,
+        physics_hooks: &dyn PhysicsHooks,
+        event_handler: &dyn EventHandler,
+    ) {
+        let mut integration_parameters = IntegrationParameters::default();
+        integration_parameters.length_unit = settings.length_unit;
+        integration_parameters.dt = settings.dt;
+        integration_parameters.max_ccd_substeps = settings.max_ccd_substeps;
+        if settings.num_solver_iterations > 0 {
+            integration_parameters.num_solver_iterations =
+                NonZeroUsize::new(settings.num_solver_iterations).unwrap();
+        }
+        integration_parameters.num_additional_friction_iterations =
+            settings.num_additional_friction_iterations;
+        integration_parameters.num_internal_pgs_iterations = settings.num_internal_pgs_iterations;
+        let gravity = settings.pixel_gravity;
+        let liquid_gravity = settings.pixel_liquid_gravity;
+        self.physics_pipeline.step(
+            &gravity,
+            &integration_parameters,
+            &mut self.physics_objects.island_manager,
+            &mut self.physics_objects.broad_phase,
+            &mut self.physics_objects.narrow_phase,
+            &mut self.physics_objects.rigid_body_set,
+            &mut self.physics_objects.collider_set,
+            &mut self.physics_objects.impulse_joint_set,
+            &mut self.physics_objects.multibody_joint_set,
+            &mut self.physics_objects.ccd_solver,
+            Some(&mut self.physics_objects.query_pipeline),
+            physics_hooks,
+            event_handler,
+        );
+        if self.fluids_pipeline.liquid_world.fluids().len() > 0 {
+            self.fluids_pipeline.step(
+                &liquid_gravity,
+                integration_parameters.dt,
+                &self.physics_objects.collider_set,
+                &mut self.physics_objects.rigid_body_set,
+            );
+        }
+    }
+
     pub fn insert_collider(
         &mut self,
         collider: Collider,
@@ -350,10 +372,35 @@ impl PhysicsWorld {
             .remove(joint_handle, true);
     }
 }
-#[derive(Default)]
+// Larger compounds are not shared, comparing them costs more than sharing saves
+const MAX_INTERNED_COMPOUND_SHAPES: usize = 64;
+struct InternedShape {
+    hash: u64,
+    ref_count: usize,
+}
+pub struct ShapeDedupStats {
+    pub unique_shapes: usize,
+    pub shape_references: usize,
+}
 pub struct PhysicsEngine {
     pub physics_worlds: Arena<PhysicsWorld>,
     pub shapes: Arena<SharedShape>,
+    pub shape_subdivisions: u32,
+    // Shapes with identical content share one handle, and are removed when no longer referenced.
+    // Each hash keeps all the shapes with it, in case of collisions.
+    interned_shapes: HashMap<ShapeHandle, InternedShape>,
+    shape_hashes: HashMap<u64, Vec<ShapeHandle>>,
+}
+impl Default for PhysicsEngine {
+    fn default() -> Self {
+        Self {
+            physics_worlds: Arena::default(),
+            shapes: Arena::default(),
+            shape_subdivisions: DEFAULT_SHAPE_SUBDIVISIONS,
+            interned_shapes: HashMap::default(),
+            shape_hashes: HashMap::default(),
+        }
+    }
 }
 impl PhysicsEngine {
     pub fn get_mut_world(&mut self, world_handle: WorldHandle) -> Option<&mut PhysicsWorld> {
@@ -365,13 +412,67 @@ impl PhysicsEngine {
     }
 
     pub fn insert_shape(&mut self, shape: SharedShape) -> ShapeHandle {
+        if shape
+            .as_compound()
+            .is_some_and(|compound| compound.shapes().len() > MAX_INTERNED_COMPOUND_SHAPES)
+        {
+            return self.insert_unique_shape(shape);
+        }
+        let Some(hash) = shape_content_hash(shape.as_ref()) else {
+            return self.insert_unique_shape(shape);
+        };
+        let shape_handles = self.shape_hashes.entry(hash).or_default();
+        for shape_handle in shape_handles.iter() {
+            if let Some(interned_shape) = self.interned_shapes.get_mut(shape_handle)
+                && let Some(existing_shape) = self.shapes.get(*shape_handle)
+                && shape_content_eq(existing_shape.as_ref(), shape.as_ref())
+            {
+                interned_shape.ref_count += 1;
+                return *shape_handle;
+            }
+        }
+        let shape_handle = self.shapes.insert(shape);
+        shape_handles.push(shape_handle);
+        self.interned_shapes
+            .insert(shape_handle, InternedShape { hash, ref_count: 1 });
+        shape_handle
+    }
+
+    // For shapes that are unlikely to be shared and expensive to compare, eg. voxels and merged
+    // tiles
+    pub fn insert_unique_shape(&mut self, shape: SharedShape) -> ShapeHandle {
         self.shapes.insert(shape)
     }
 
     pub fn remove_shape(&mut self, shape_handle: ShapeHandle) {
+        if let Some(interned_shape) = self.interned_shapes.get_mut(&shape_handle) {
+            interned_shape.ref_count -= 1;
+            if interned_shape.ref_count > 0 {
+                return;
+            }
+            let hash = interned_shape.hash;
+            self.interned_shapes.remove(&shape_handle);
+            if let Some(shape_handles) = self.shape_hashes.get_mut(&hash) {
+                shape_handles.retain(|handle| *handle != shape_handle);
+                if shape_handles.is_empty() {
+                    self.shape_hashes.remove(&hash);
+                }
+            }
+        }
         self.shapes.remove(shape_handle);
     }
 
+    pub fn get_shape_dedup_stats(&self) -> ShapeDedupStats {
+        let mut shape_references = self.shapes.len();
+        for interned_shape in self.interned_shapes.values() {
+            shape_references += interned_shape.ref_count - 1;
+        }
+        ShapeDedupStats {
+            unique_shapes: self.shapes.len(),
+            shape_references,
+        }
+    }
+
     pub fn get_shape(&self, shape_handle: ShapeHandle) -> Option<&SharedShape> {
         self.shapes.get(shape_handle)
     }
@@ -408,7 +509,7 @@ impl PhysicsEngine {
 
     pub fn print_stats(&mut self, world_handle: WorldHandle) {
         if let Some(physics_world) = self.get_mut_world(world_handle) {
-            godot_print!(
+            physics_print!(
                 "{} {} {}",
                 physics_world.physics_objects.collider_set.len(),
                 physics_world.physics_objects.rigid_body_set.len(),
@@ -428,13 +529,90 @@ impl PhysicsEngine {
         0
     }
 
-    pub fn world_export(&mut self, world_handle: WorldHandle) -> Option<&PhysicsObjects> {
-        if let Some(physics_world) = self.get_mut_world(world_handle) {
+    pub fn world_export(&self, world_handle: WorldHandle) -> Option<&PhysicsObjects> {
+        if let Some(physics_world) = self.get_world(world_handle) {
             return Some(&physics_world.physics_objects);
         }
         None
     }
 
+    // Replaces the objects of a world with imported ones. The skipped pipelines are rebuilt by
+    // inserting everything again, so the handles can change. Bodies and colliders whose owner is
+    // not known are dropped, along with their joints. Returns the old to new joint handles and
+    // the number of dropped bodies and colliders.
+    pub fn world_import(
+        &mut self,
+        world_handle: WorldHandle,
+        physics_objects: PhysicsObjects,
+        is_owner_known: impl Fn(&UserData) -> bool,
+    ) -> (HashMap<ImpulseJointHandle, ImpulseJointHandle>, usize) {
+        let mut joint_handles = HashMap::default();
+        let mut dropped_objects = 0;
+        let Some(physics_world) = self.get_mut_world(world_handle) else {
+            return (joint_handles, dropped_objects);
+        };
+        for (collider_handle, _) in physics_world.physics_objects.collider_set.iter() {
+            physics_world
+                .fluids_pipeline
+                .coupling
+                .unregister_coupling(collider_handle);
+        }
+        let mut new_physics_objects = PhysicsObjects::new(world_handle);
+        let mut body_handles = HashMap::default();
+        for (body_handle, body) in physics_objects.rigid_body_set.iter() {
+            if !is_owner_known(&UserData::new(body.user_data)) {
+                dropped_objects += 1;
+                continue;
+            }
+            let new_body_handle = new_physics_objects.rigid_body_set.insert(body.clone());
+            body_handles.insert(body_handle, new_body_handle);
+        }
+        for (_, collider) in physics_objects.collider_set.iter() {
+            if !is_owner_known(&UserData::new(collider.user_data)) {
+                dropped_objects += 1;
+                continue;
+            }
+            let collider_handle = match collider.parent() {
+                Some(parent) => {
+                    let Some(new_parent) = body_handles.get(&parent) else {
+                        dropped_objects += 1;
+                        continue;
+                    };
+                    new_physics_objects.collider_set.insert_with_parent(
+                        collider.clone(),
+                        *new_parent,
+                        &mut new_physics_objects.rigid_body_set,
+                    )
+                }
+                None => new_physics_objects.collider_set.insert(collider.clone()),
+            };
+            // register fluid coupling again, same as when creating a solid collider
+            if !collider.is_sensor() && !shape_is_halfspace(collider.shared_shape()) {
+                let boundary_handle = physics_world
+                    .fluids_pipeline
+                    .liquid_world
+                    .add_boundary(Boundary::new(Vec::new()));
+                physics_world.fluids_pipeline.coupling.register_coupling(
+                    boundary_handle,
+                    collider_handle,
+                    ColliderSampling::DynamicContactSampling,
+                );
+            }
+        }
+        for (joint_handle, joint) in physics_objects.impulse_joint_set.iter() {
+            if let Some(body1) = body_handles.get(&joint.body1)
+                && let Some(body2) = body_handles.get(&joint.body2)
+            {
+                let new_joint_handle = new_physics_objects
+                    .impulse_joint_set
+                    .insert(*body1, *body2, joint.data, true);
+                joint_handles.insert(joint_handle, new_joint_handle);
+            }
+        }
+        physics_world.physics_objects = new_physics_objects;
+        (joint_handles, dropped_objects)
+    }
+
     pub fn world_step(
         &mut self,
         world_handle: WorldHandle,
@@ -455,3 +633,70 @@ impl PhysicsEngine {
         }
     }
 }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const KNOWN_OWNER: u128 = 1;
+    const UNKNOWN_OWNER: u128 = 2;
+    fn world_settings() -> WorldSettings {
+        WorldSettings {
+            particle_radius: 1.0,
+            smoothing_factor: 2.0,
+            counters_enabled: false,
+        }
+    }
+    fn insert_body(physics_objects: &mut PhysicsObjects, owner: u128) -> RigidBodyHandle {
+        let body_handle = physics_objects
+            .rigid_body_set
+            .insert(RigidBodyBuilder::dynamic().user_data(owner).build());
+        physics_objects.collider_set.insert_with_parent(
+            ColliderBuilder::ball(1.0).user_data(owner).build(),
+            body_handle,
+            &mut physics_objects.rigid_body_set,
+        );
+        body_handle
+    }
+    #[test]
+    fn world_import_drops_objects_with_unknown_owners() {
+        let mut physics_engine = PhysicsEngine::default();
+        let world_handle = physics_engine.world_create(&world_settings());
+        let mut physics_objects = PhysicsObjects::new(WorldHandle::default());
+        let known_body = insert_body(&mut physics_objects, KNOWN_OWNER);
+        let other_known_body = insert_body(&mut physics_objects, KNOWN_OWNER);
+        let unknown_body = insert_body(&mut physics_objects, UNKNOWN_OWNER);
+        physics_objects
+            .collider_set
+            .insert(ColliderBuilder::ball(1.0).user_data(UNKNOWN_OWNER).build());
+        let known_joint = physics_objects.impulse_joint_set.insert(
+            known_body,
+            other_known_body,
+            FixedJointBuilder::new(),
+            true,
+        );
+        let unknown_joint = physics_objects.impulse_joint_set.insert(
+            known_body,
+            unknown_body,
+            FixedJointBuilder::new(),
+            true,
+        );
+        let (joint_handles, dropped_objects) =
+            physics_engine.world_import(world_handle, physics_objects, |user_data| {
+                user_data.get_data() == KNOWN_OWNER
+            });
+        // The unknown body, its collider and the static collider
+        assert_eq!(dropped_objects, 3);
+        assert!(joint_handles.contains_key(&known_joint));
+        assert!(!joint_handles.contains_key(&unknown_joint));
+        let physics_objects = &physics_engine
+            .get_world(world_handle)
+            .unwrap()
+            .physics_objects;
+        assert_eq!(physics_objects.rigid_body_set.len(), 2);
+        assert_eq!(physics_objects.collider_set.len(), 2);
+        assert_eq!(physics_objects.impulse_joint_set.len(), 1);
+        for (_, collider) in physics_objects.collider_set.iter() {
+            assert_eq!(collider.user_data, KNOWN_OWNER);
+            assert!(collider.parent().is_some());
+        }
+    }
+}
@@ -1,6 +1,8 @@
 pub use super::body::*;
+pub use super::character::*;
 pub use super::collider::*;
 pub use super::convert::*;
+pub use super::delta::*;
 pub use super::event_handler::*;
 pub use super::handle::*;
 pub use super::physics_hooks::*;
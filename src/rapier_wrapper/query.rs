@@ -1,11 +1,11 @@
 use std::ops::Mul;
 
-use godot::log::godot_error;
 use nalgebra::zero;
 use rapier::parry;
 use rapier::parry::query::ShapeCastOptions;
 use rapier::prelude::*;
 
+use crate::rapier_wrapper::logging::physics_error;
 use crate::rapier_wrapper::prelude::*;
 use crate::servers::rapier_physics_server_extra::PhysicsCollisionObjects;
 use crate::spaces::rapier_space::RapierSpace;
@@ -224,9 +224,11 @@ impl PhysicsEngine {
         }
         let mut result = ShapeCastResult::new();
         if let Some(raw_shared_shape1) = self.get_shape(shape_info1.handle) {
-            let shared_shape1 = scale_shape(raw_shared_shape1, shape_info1);
+            let shared_shape1 =
+                scale_shape(raw_shared_shape1, shape_info1, self.shape_subdivisions);
             if let Some(raw_shared_shape2) = self.get_shape(shape_info2.handle) {
-                let shared_shape2 = scale_shape(raw_shared_shape2, shape_info2);
+                let shared_shape2 =
+                    scale_shape(raw_shared_shape2, shape_info2, self.shape_subdivisions);
                 let shape_transform1 = shape_info1.transform;
                 let shape_transform2 = shape_info2.transform;
                 let mut shape_cast_options = ShapeCastOptions::default();
@@ -253,7 +255,7 @@ impl PhysicsEngine {
                         result.pixel_witness2 = hit.witness2.coords;
                     }
                     Err(err) => {
-                        godot_error!("toi error: {:?}", err);
+                        physics_error!("toi error: {:?}", err);
                     }
                 }
             }
@@ -278,7 +280,7 @@ impl PhysicsEngine {
         }
         let mut result = ShapeCastResult::new();
         if let Some(raw_shared_shape) = self.get_shape(shape_info.handle) {
-            let shared_shape = scale_shape(raw_shared_shape, shape_info);
+            let shared_shape = scale_shape(raw_shared_shape, shape_info, self.shape_subdivisions);
             if let Some(physics_world) = self.get_world(world_handle) {
                 let shape_transform = shape_info.transform;
                 let mut filter = QueryFilter::new();
@@ -331,7 +333,7 @@ impl PhysicsEngine {
                     {
                         witness2 += collider.position().translation.vector;
                     } else {
-                        godot_error!("collider not found");
+                        physics_error!("collider not found");
                     }
                     result.pixel_witness1 = witness1.coords;
                     result.pixel_witness2 = witness2.coords;
@@ -408,9 +410,11 @@ impl PhysicsEngine {
         //let prediction = Real::max(0.002, margin);
         let prediction = margin;
         if let Some(raw_shared_shape1) = self.get_shape(shape_info1.handle) {
-            let shared_shape1 = scale_shape(raw_shared_shape1, shape_info1);
+            let shared_shape1 =
+                scale_shape(raw_shared_shape1, shape_info1, self.shape_subdivisions);
             if let Some(raw_shared_shape2) = self.get_shape(shape_info2.handle) {
-                let shared_shape2 = scale_shape(raw_shared_shape2, shape_info2);
+                let shared_shape2 =
+                    scale_shape(raw_shared_shape2, shape_info2, self.shape_subdivisions);
                 let shape_transform1 = shape_info1.transform;
                 let shape_transform2 = shape_info2.transform;
                 if let Ok(Some(contact)) = parry::query::contact(
@@ -19,3 +19,19 @@ pub struct SimulationSettings {
     pub pixel_liquid_gravity: Vector<Real>,
     pub max_ccd_substeps: usize,
 }
+// Settings a space was stepped with, stored in space exports so a replay can use them
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpaceSettingsExport {
+    pub length_unit: Real,
+    pub num_solver_iterations: usize,
+    pub num_additional_friction_iterations: usize,
+    pub num_internal_pgs_iterations: usize,
+    pub max_ccd_substeps: usize,
+    pub pixel_gravity: Vector<Real>,
+    pub fluid_particle_radius: Real,
+    pub fluid_smoothing_factor: Real,
+}
@@ -1,3 +1,5 @@
+#[cfg(feature = "dim2")]
+use hashbrown::HashMap;
 use rapier::prelude::*;
 use types::Transform;
 
@@ -11,6 +13,41 @@ pub fn point_array_to_vec(pixel_data: &Vec<Vector<Real>>) -> Vec<Point<Real>> {
     }
     vec
 }
+pub const MESH_FLAG_MERGE_DUPLICATE_VERTICES: u32 = 1;
+pub const MESH_FLAG_FIX_INTERNAL_EDGES: u32 = 1 << 1;
+pub fn trimesh_flags(mesh_flags: u32) -> TriMeshFlags {
+    let mut flags = TriMeshFlags::empty();
+    if mesh_flags & MESH_FLAG_MERGE_DUPLICATE_VERTICES != 0 {
+        flags |= TriMeshFlags::MERGE_DUPLICATE_VERTICES;
+    }
+    if mesh_flags & MESH_FLAG_FIX_INTERNAL_EDGES != 0 {
+        flags |= TriMeshFlags::FIX_INTERNAL_EDGES;
+    }
+    flags
+}
+// Segments are given as pairs of points. Equal points are merged with a hash lookup, so the
+// segments that share a point are connected.
+#[cfg(feature = "dim2")]
+fn merge_segment_vertices(points: &[Point<Real>]) -> (Vec<Point<Real>>, Vec<[u32; 2]>) {
+    let mut merged_points = Vec::with_capacity(points.len());
+    let mut point_indices = HashMap::with_capacity(points.len());
+    let mut indices = Vec::with_capacity(points.len() / 2);
+    for segment in points.chunks_exact(2) {
+        let mut segment_indices = [0; 2];
+        for (i, point) in segment.iter().enumerate() {
+            // Adding zero turns -0 into 0, so both have the same bits
+            let key = point.coords.map(|coord| (coord + 0.0).to_bits());
+            segment_indices[i] = *point_indices.entry(key).or_insert_with(|| {
+                merged_points.push(*point);
+                merged_points.len() as u32 - 1
+            });
+        }
+        if segment_indices[0] != segment_indices[1] {
+            indices.push(segment_indices);
+        }
+    }
+    (merged_points, indices)
+}
 #[derive(Copy, Clone, Debug)]
 pub struct ShapeInfo {
     pub handle: ShapeHandle,
@@ -99,13 +136,249 @@ impl PhysicsEngine {
         self.insert_shape(shape)
     }
 
+    #[cfg(feature = "dim3")]
+    pub fn shape_create_round_cylinder(
+        &mut self,
+        half_height: Real,
+        radius: Real,
+        border_radius: Real,
+    ) -> ShapeHandle {
+        let shape = SharedShape::round_cylinder(half_height, radius, border_radius);
+        self.insert_shape(shape)
+    }
+
+    #[cfg(feature = "dim3")]
+    pub fn shape_create_round_cone(
+        &mut self,
+        half_height: Real,
+        radius: Real,
+        border_radius: Real,
+    ) -> ShapeHandle {
+        let shape = SharedShape::round_cone(half_height, radius, border_radius);
+        self.insert_shape(shape)
+    }
+
+    pub fn shape_create_round_triangle(
+        &mut self,
+        a: Vector<Real>,
+        b: Vector<Real>,
+        c: Vector<Real>,
+        border_radius: Real,
+    ) -> ShapeHandle {
+        let shape = SharedShape::round_triangle(
+            Point::from(a),
+            Point::from(b),
+            Point::from(c),
+            border_radius,
+        );
+        self.insert_shape(shape)
+    }
+
+    #[cfg(feature = "dim3")]
+    pub fn shape_create_cone(&mut self, half_height: Real, radius: Real) -> ShapeHandle {
+        let shape = SharedShape::cone(half_height, radius);
+        self.insert_shape(shape)
+    }
+
+    #[cfg(feature = "dim3")]
+    pub fn shape_create_segment(&mut self, a: Vector<Real>, b: Vector<Real>) -> ShapeHandle {
+        let shape = SharedShape::segment(Point::from(a), Point::from(b));
+        self.insert_shape(shape)
+    }
+
+    pub fn shape_create_triangle(
+        &mut self,
+        a: Vector<Real>,
+        b: Vector<Real>,
+        c: Vector<Real>,
+    ) -> ShapeHandle {
+        let shape = SharedShape::triangle(Point::from(a), Point::from(b), Point::from(c));
+        self.insert_shape(shape)
+    }
+
+    #[cfg(feature = "dim2")]
+    pub fn shape_create_round_box(
+        &mut self,
+        size: Vector<Real>,
+        border_radius: Real,
+    ) -> ShapeHandle {
+        let shape = SharedShape::round_cuboid(0.5 * size.x, 0.5 * size.y, border_radius);
+        self.insert_shape(shape)
+    }
+
+    #[cfg(feature = "dim3")]
+    pub fn shape_create_round_box(
+        &mut self,
+        size: Vector<Real>,
+        border_radius: Real,
+    ) -> ShapeHandle {
+        let shape =
+            SharedShape::round_cuboid(0.5 * size.x, 0.5 * size.y, 0.5 * size.z, border_radius);
+        self.insert_shape(shape)
+    }
+
+    #[cfg(feature = "dim2")]
+    pub fn shape_create_round_convex_polyline(
+        &mut self,
+        points: &Vec<Vector<Real>>,
+        border_radius: Real,
+    ) -> ShapeHandle {
+        let points_vec = point_array_to_vec(points);
+        if let Some(shape_data) = SharedShape::round_convex_polyline(points_vec, border_radius) {
+            return self.insert_shape(shape_data);
+        }
+        ShapeHandle::default()
+    }
+
+    #[cfg(feature = "dim3")]
+    pub fn shape_create_round_convex_polyline(
+        &mut self,
+        points: &Vec<Vector<Real>>,
+        border_radius: Real,
+    ) -> ShapeHandle {
+        let points_vec = point_array_to_vec(points);
+        if let Some(shape_data) = SharedShape::round_convex_hull(&points_vec, border_radius) {
+            return self.insert_shape(shape_data);
+        }
+        ShapeHandle::default()
+    }
+
+    #[cfg(feature = "dim3")]
+    pub fn shape_create_heightmap(
+        &mut self,
+        heights: &[Real],
+        width: usize,
+        depth: usize,
+        mesh_flags: u32,
+    ) -> ShapeHandle {
+        use nalgebra::DMatrix;
+        // Godot stores the heights row by row along z, with a cell size of 1
+        let heights = DMatrix::from_fn(depth, width, |z, x| heights[z * width + x]);
+        let scale = Vector::new((width - 1) as Real, 1.0, (depth - 1) as Real);
+        let mut flags = HeightFieldFlags::empty();
+        if mesh_flags & MESH_FLAG_FIX_INTERNAL_EDGES != 0 {
+            flags |= HeightFieldFlags::FIX_INTERNAL_EDGES;
+        }
+        let shape = SharedShape::heightfield_with_flags(heights, scale, flags);
+        self.insert_shape(shape)
+    }
+
     pub fn shape_create_concave_polyline(&mut self, points: &Vec<Vector<Real>>) -> ShapeHandle {
         let points_vec = point_array_to_vec(points);
         let shape = SharedShape::polyline(points_vec, None);
         self.insert_shape(shape)
     }
 
+    // Polyline of separate segments, given as pairs of points, connected where they share a point
+    #[cfg(feature = "dim2")]
+    pub fn shape_create_concave_segments(&mut self, points: &Vec<Vector<Real>>) -> ShapeHandle {
+        let (points_vec, indices) = merge_segment_vertices(&point_array_to_vec(points));
+        let shape = SharedShape::polyline(points_vec, Some(indices));
+        self.insert_shape(shape)
+    }
+
+    #[cfg(feature = "dim3")]
+    pub fn shape_create_concave_trimesh(
+        &mut self,
+        faces: &Vec<Vector<Real>>,
+        backface_collision: bool,
+        mesh_flags: u32,
+    ) -> ShapeHandle {
+        let points_vec = point_array_to_vec(faces);
+        // Godot faces are clockwise, parry expects them counter clockwise
+        let mut indices = Vec::with_capacity(faces.len() / 3);
+        for i in (0..faces.len() as u32 / 3).map(|i| i * 3) {
+            indices.push([i, i + 2, i + 1]);
+        }
+        // An oriented mesh only pushes objects out through the front faces
+        let mut flags = trimesh_flags(mesh_flags);
+        if !backface_collision {
+            flags |= TriMeshFlags::ORIENTED;
+        }
+        let shape = SharedShape::trimesh_with_flags(points_vec, indices, flags);
+        self.insert_shape(shape)
+    }
+
+    // Compound of boxes, each given by its center and half extents
+    pub fn shape_create_box_compound(
+        &mut self,
+        boxes: &[(Vector<Real>, Vector<Real>)],
+    ) -> ShapeHandle {
+        if boxes.is_empty() {
+            return ShapeHandle::default();
+        }
+        let mut shapes_vec = Vec::with_capacity(boxes.len());
+        for (center, half_extents) in boxes {
+            let shape = SharedShape::new(Cuboid::new(*half_extents));
+            shapes_vec.push((Isometry::new(*center, ANG_ZERO), shape));
+        }
+        let shape_compound = SharedShape::compound(shapes_vec);
+        self.insert_unique_shape(shape_compound)
+    }
+
+    // Compound of the sub shapes of other compounds, which are shared instead of created again
+    pub fn shape_create_merged_compound(
+        &mut self,
+        compound_handles: &[ShapeHandle],
+    ) -> ShapeHandle {
+        let mut shapes_vec = Vec::new();
+        for compound_handle in compound_handles {
+            if let Some(compound) = self
+                .get_shape(*compound_handle)
+                .and_then(|shape| shape.as_compound())
+            {
+                shapes_vec.extend(compound.shapes().iter().cloned());
+            }
+        }
+        if shapes_vec.is_empty() {
+            return ShapeHandle::default();
+        }
+        let shape_compound = SharedShape::compound(shapes_vec);
+        self.insert_unique_shape(shape_compound)
+    }
+
+    // Splits a concave shape into a compound of convex hulls, so it can be used by dynamic bodies
+    pub fn shape_create_convex_decomposition(&mut self, shape_handle: ShapeHandle) -> ShapeHandle {
+        let Some(shape) = self.get_shape(shape_handle) else {
+            return ShapeHandle::default();
+        };
+        let decomposed_shape = match shape.shape_type() {
+            #[cfg(feature = "dim2")]
+            ShapeType::Polyline => shape.as_polyline().map(|polyline| {
+                SharedShape::convex_decomposition(polyline.vertices(), polyline.indices())
+            }),
+            #[cfg(feature = "dim3")]
+            ShapeType::TriMesh => shape.as_trimesh().map(|trimesh| {
+                SharedShape::convex_decomposition(trimesh.vertices(), trimesh.indices())
+            }),
+            _ => None,
+        };
+        match decomposed_shape {
+            Some(decomposed_shape) => self.insert_shape(decomposed_shape),
+            None => ShapeHandle::default(),
+        }
+    }
+
     pub fn shape_destroy(&mut self, shape_handle: ShapeHandle) {
         self.remove_shape(shape_handle)
     }
 }
+#[cfg(all(test, feature = "dim2"))]
+mod tests {
+    use super::*;
+    #[test]
+    fn merge_segment_vertices_connects_shared_points() {
+        let points = [
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.0),
+            Point::new(1.0, 0.0),
+            Point::new(1.0, 1.0),
+            Point::new(-0.0, 2.0),
+            Point::new(0.0, 2.0),
+        ];
+        let (merged_points, indices) = merge_segment_vertices(&points);
+        assert_eq!(merged_points.len(), 4);
+        // The last segment has both points merged into one, so it is dropped
+        assert_eq!(indices, vec![[0, 1], [1, 2]]);
+    }
+}
@@ -0,0 +1,231 @@
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+use std::time::Instant;
+
+use rapier::math::Real;
+use rapier::math::Vector;
+
+use crate::rapier_wrapper::fluid::FluidExport;
+use crate::rapier_wrapper::prelude::*;
+use crate::servers::rapier_physics_server_export::*;
+// Only the world part of a space export is needed, the rest needs the engine to be loaded
+#[derive(serde::Deserialize)]
+struct ReplaySpace {
+    inner: PhysicsObjects,
+    #[serde(default)]
+    liquid_world: Vec<FluidExport>,
+    #[serde(default)]
+    settings: Option<SpaceSettingsExport>,
+}
+// Options that are not set use the settings stored in the export.
+// Exports without settings use the project settings defaults, with no gravity.
+pub struct ReplayOptions {
+    pub steps: usize,
+    pub dt: f64,
+    pub length_unit: Option<f64>,
+    pub num_solver_iterations: Option<usize>,
+    pub num_additional_friction_iterations: Option<usize>,
+    pub num_internal_pgs_iterations: Option<usize>,
+    pub max_ccd_substeps: Option<usize>,
+    pub gravity: Vec<f64>,
+    pub liquid_gravity: Vec<f64>,
+    pub fluid_particle_radius: Option<f64>,
+    pub fluid_smoothing_factor: Option<f64>,
+}
+impl Default for ReplayOptions {
+    fn default() -> Self {
+        Self {
+            steps: 60,
+            dt: 1.0 / 60.0,
+            length_unit: None,
+            num_solver_iterations: None,
+            num_additional_friction_iterations: None,
+            num_internal_pgs_iterations: None,
+            max_ccd_substeps: None,
+            gravity: Vec::new(),
+            liquid_gravity: Vec::new(),
+            fluid_particle_radius: None,
+            fluid_smoothing_factor: None,
+        }
+    }
+}
+fn default_settings() -> SpaceSettingsExport {
+    SpaceSettingsExport {
+        #[cfg(feature = "dim2")]
+        length_unit: 100.0,
+        #[cfg(feature = "dim3")]
+        length_unit: 1.0,
+        num_solver_iterations: 4,
+        num_additional_friction_iterations: 4,
+        num_internal_pgs_iterations: 1,
+        max_ccd_substeps: 1,
+        pixel_gravity: Vector::zeros(),
+        fluid_particle_radius: 20.0,
+        fluid_smoothing_factor: 2.0,
+    }
+}
+pub struct ReplayReport {
+    pub steps: usize,
+    pub load_time: Duration,
+    pub total_time: Duration,
+    pub min_step_time: Duration,
+    pub max_step_time: Duration,
+    pub bodies: usize,
+    pub active_bodies: usize,
+    pub sleeping_bodies: usize,
+    pub colliders: usize,
+    pub joints: usize,
+    pub fluid_particles: usize,
+    pub state_hash: u64,
+}
+impl fmt::Display for ReplayReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mean_step_time = if self.steps > 0 {
+            self.total_time / self.steps as u32
+        } else {
+            Duration::ZERO
+        };
+        writeln!(f, "load time: {:?}", self.load_time)?;
+        writeln!(f, "steps: {}", self.steps)?;
+        writeln!(f, "total step time: {:?}", self.total_time)?;
+        writeln!(
+            f,
+            "step time: min {:?}, mean {:?}, max {:?}",
+            self.min_step_time, mean_step_time, self.max_step_time
+        )?;
+        writeln!(
+            f,
+            "bodies: {} ({} active, {} sleeping)",
+            self.bodies, self.active_bodies, self.sleeping_bodies
+        )?;
+        writeln!(f, "colliders: {}", self.colliders)?;
+        writeln!(f, "joints: {}", self.joints)?;
+        writeln!(f, "fluid particles: {}", self.fluid_particles)?;
+        write!(f, "state hash: {:016x}", self.state_hash)
+    }
+}
+fn options_vector(values: &[f64], default: Vector<Real>) -> Result<Vector<Real>, String> {
+    if values.is_empty() {
+        return Ok(default);
+    }
+    let mut vector = Vector::zeros();
+    if values.len() != vector.len() {
+        return Err(format!(
+            "Expected {} components for a vector, got {}",
+            vector.len(),
+            values.len()
+        ));
+    }
+    for (i, value) in values.iter().enumerate() {
+        vector[i] = *value as Real;
+    }
+    Ok(vector)
+}
+fn load_space(path: &Path) -> Result<ReplaySpace, String> {
+    let data = std::fs::read(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    let is_json = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+    let space = if is_json {
+        let data = String::from_utf8(data).map_err(|e| e.to_string())?;
+        import_json::<ReplaySpace>(ExportKind::Space, &data)
+    } else {
+        import_binary::<ReplaySpace>(ExportKind::Space, &data)
+    };
+    space.map_err(|e| e.to_string())
+}
+pub fn run(path: &Path, options: &ReplayOptions) -> Result<ReplayReport, String> {
+    let load_start = Instant::now();
+    let space = load_space(path)?;
+    let exported = space.settings.unwrap_or_else(default_settings);
+    let pixel_gravity = options_vector(&options.gravity, exported.pixel_gravity)?;
+    let settings = SimulationSettings {
+        dt: options.dt as Real,
+        length_unit: options
+            .length_unit
+            .map_or(exported.length_unit, |length_unit| length_unit as Real),
+        num_solver_iterations: options
+            .num_solver_iterations
+            .unwrap_or(exported.num_solver_iterations),
+        num_additional_friction_iterations: options
+            .num_additional_friction_iterations
+            .unwrap_or(exported.num_additional_friction_iterations),
+        num_internal_pgs_iterations: options
+            .num_internal_pgs_iterations
+            .unwrap_or(exported.num_internal_pgs_iterations),
+        pixel_gravity,
+        // The server steps fluids with the same gravity as bodies
+        pixel_liquid_gravity: options_vector(&options.liquid_gravity, pixel_gravity)?,
+        max_ccd_substeps: options
+            .max_ccd_substeps
+            .unwrap_or(exported.max_ccd_substeps),
+    };
+    let mut physics_engine = PhysicsEngine::default();
+    let world_handle = physics_engine.world_create(&WorldSettings {
+        particle_radius: options
+            .fluid_particle_radius
+            .map_or(exported.fluid_particle_radius, |radius| radius as Real),
+        smoothing_factor: options
+            .fluid_smoothing_factor
+            .map_or(exported.fluid_smoothing_factor, |factor| factor as Real),
+        counters_enabled: false,
+    });
+    // There are no Godot objects to relink, so every object is kept
+    physics_engine.world_import(world_handle, space.inner, |_| true);
+    physics_engine.world_import_fluids(world_handle, space.liquid_world);
+    let load_time = load_start.elapsed();
+    let Some(physics_world) = physics_engine.get_mut_world(world_handle) else {
+        return Err("Failed to create world".to_string());
+    };
+    let mut total_time = Duration::ZERO;
+    let mut min_step_time = Duration::MAX;
+    let mut max_step_time = Duration::ZERO;
+    for _ in 0..options.steps {
+        let step_start = Instant::now();
+        physics_world.step_pipelines(&settings, &(), &());
+        let step_time = step_start.elapsed();
+        total_time += step_time;
+        min_step_time = min_step_time.min(step_time);
+        max_step_time = max_step_time.max(step_time);
+    }
+    if options.steps == 0 {
+        min_step_time = Duration::ZERO;
+    }
+    let physics_objects = &physics_world.physics_objects;
+    let bodies = physics_objects.rigid_body_set.len();
+    let active_bodies = physics_objects.island_manager.active_dynamic_bodies().len()
+        + physics_objects
+            .island_manager
+            .active_kinematic_bodies()
+            .len();
+    let colliders = physics_objects.collider_set.len();
+    let joints = physics_objects.impulse_joint_set.len();
+    let sleeping_bodies = physics_objects
+        .rigid_body_set
+        .iter()
+        .filter(|(_, body)| body.is_sleeping())
+        .count();
+    let fluid_particles = physics_world
+        .fluids_pipeline
+        .liquid_world
+        .fluids()
+        .iter()
+        .map(|(_, fluid)| fluid.positions.len())
+        .sum();
+    let state_hash = physics_engine.world_get_state_hash(world_handle);
+    Ok(ReplayReport {
+        steps: options.steps,
+        load_time,
+        total_time,
+        min_step_time,
+        max_step_time,
+        bodies,
+        active_bodies,
+        sleeping_bodies,
+        colliders,
+        joints,
+        fluid_particles,
+        state_hash,
+    })
+}
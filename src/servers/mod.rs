@@ -5,8 +5,13 @@ use crate::servers::rapier_project_settings::RapierProjectSettings;
 pub mod rapier_physics_server_2d;
 #[cfg(feature = "dim3")]
 pub mod rapier_physics_server_3d;
+#[cfg(feature = "serde-serialize")]
+pub mod rapier_physics_server_export;
 pub mod rapier_physics_server_extra;
 pub mod rapier_physics_server_impl;
+pub mod rapier_physics_server_record;
+#[cfg(feature = "serde-serialize")]
+pub mod rapier_physics_server_state;
 pub mod rapier_project_settings;
 #[cfg(feature = "dim2")]
 pub type RapierPhysicsServer = rapier_physics_server_2d::RapierPhysicsServer2D;
@@ -419,6 +419,14 @@ impl IPhysicsServer3DExtension for RapierPhysicsServer3D {
             .body_set_axis_velocity(body, axis_velocity);
     }
 
+    fn body_set_axis_lock(&mut self, body: Rid, axis: BodyAxis, lock: bool) {
+        self.implementation.body_set_axis_lock(body, axis, lock);
+    }
+
+    fn body_is_axis_locked(&self, body: Rid, axis: BodyAxis) -> bool {
+        self.implementation.body_is_axis_locked(body, axis)
+    }
+
     fn body_add_collision_exception(&mut self, body: Rid, excepted_body: Rid) {
         self.implementation
             .body_add_collision_exception(body, excepted_body);
@@ -0,0 +1,307 @@
+use std::fmt;
+
+use godot::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+// Bump this when a serialized type changes and add a migration step for it
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
+const EXPORT_MAGIC: u32 = 0x5850_4752;
+#[cfg(feature = "dim2")]
+const EXPORT_DIM: u8 = 2;
+#[cfg(feature = "dim3")]
+const EXPORT_DIM: u8 = 3;
+#[cfg(feature = "single")]
+const EXPORT_PRECISION: ExportPrecision = ExportPrecision::Single;
+#[cfg(feature = "double")]
+const EXPORT_PRECISION: ExportPrecision = ExportPrecision::Double;
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportPrecision {
+    Single,
+    Double,
+}
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportKind {
+    Space,
+    Shapes,
+    Shape,
+    Joints,
+    Joint,
+    CollisionObjects,
+    CollisionObject,
+    State,
+}
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct ExportHeader {
+    pub magic: u32,
+    pub format_version: u32,
+    pub dim: u8,
+    pub precision: ExportPrecision,
+    pub kind: ExportKind,
+}
+impl ExportHeader {
+    pub fn new(kind: ExportKind) -> Self {
+        Self {
+            magic: EXPORT_MAGIC,
+            format_version: EXPORT_FORMAT_VERSION,
+            dim: EXPORT_DIM,
+            precision: EXPORT_PRECISION,
+            kind,
+        }
+    }
+}
+#[derive(Serialize)]
+struct ExportEnvelope<'a, T: Serialize> {
+    header: ExportHeader,
+    data: &'a T,
+}
+#[derive(Debug)]
+pub enum ExportError {
+    Parse(String),
+    NotVersioned,
+    WrongKind {
+        expected: ExportKind,
+        found: ExportKind,
+    },
+    WrongDim {
+        found: u8,
+    },
+    WrongPrecision {
+        found: ExportPrecision,
+    },
+    UnsupportedVersion {
+        found: u32,
+    },
+    Migration {
+        from_version: u32,
+        reason: String,
+    },
+}
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Parse(e) => write!(f, "Failed to parse export: {}", e),
+            ExportError::NotVersioned => write!(
+                f,
+                "Export has no version header. Only json exports from older versions can be migrated"
+            ),
+            ExportError::WrongKind { expected, found } => write!(
+                f,
+                "Export contains {:?} data, but {:?} data was expected",
+                found, expected
+            ),
+            ExportError::WrongDim { found } => write!(
+                f,
+                "Export was made with the {}D plugin, but this is the {}D plugin",
+                found, EXPORT_DIM
+            ),
+            ExportError::WrongPrecision { found } => write!(
+                f,
+                "Binary export was made with {:?} precision, but this plugin uses {:?} precision",
+                found, EXPORT_PRECISION
+            ),
+            ExportError::UnsupportedVersion { found } => write!(
+                f,
+                "Export format version {} is newer than the supported version {}",
+                found, EXPORT_FORMAT_VERSION
+            ),
+            ExportError::Migration {
+                from_version,
+                reason,
+            } => write!(
+                f,
+                "Failed to migrate export from format version {}: {}",
+                from_version, reason
+            ),
+        }
+    }
+}
+pub fn export_json<T: Serialize>(kind: ExportKind, data: &T) -> String {
+    let envelope = ExportEnvelope {
+        header: ExportHeader::new(kind),
+        data,
+    };
+    match serde_json::to_string_pretty(&envelope) {
+        Ok(s) => s,
+        Err(e) => {
+            godot_error!("Failed to serialize {:?}: {}", kind, e);
+            "{}".to_string()
+        }
+    }
+}
+pub fn export_binary<T: Serialize>(kind: ExportKind, data: &T) -> PackedByteArray {
+    let envelope = ExportEnvelope {
+        header: ExportHeader::new(kind),
+        data,
+    };
+    match bincode::serialize(&envelope) {
+        Ok(binary_data) => PackedByteArray::from(binary_data.as_slice()),
+        Err(e) => {
+            godot_error!("Failed to serialize {:?}: {}", kind, e);
+            PackedByteArray::new()
+        }
+    }
+}
+fn check_header(header: &ExportHeader, kind: ExportKind) -> Result<(), ExportError> {
+    if header.magic != EXPORT_MAGIC {
+        return Err(ExportError::NotVersioned);
+    }
+    if header.format_version > EXPORT_FORMAT_VERSION {
+        return Err(ExportError::UnsupportedVersion {
+            found: header.format_version,
+        });
+    }
+    if header.dim != EXPORT_DIM {
+        return Err(ExportError::WrongDim { found: header.dim });
+    }
+    if header.kind != kind {
+        return Err(ExportError::WrongKind {
+            expected: kind,
+            found: header.kind,
+        });
+    }
+    Ok(())
+}
+// Json exports store numbers as text, so they can be read with either precision.
+// Exports without a header are treated as format version 0.
+pub fn import_json<T: DeserializeOwned>(kind: ExportKind, data: &str) -> Result<T, ExportError> {
+    let mut value: Value =
+        serde_json::from_str(data).map_err(|e| ExportError::Parse(e.to_string()))?;
+    let mut format_version = 0;
+    if let Some(header) = value.get("header").cloned() {
+        let header: ExportHeader =
+            serde_json::from_value(header).map_err(|e| ExportError::Parse(e.to_string()))?;
+        check_header(&header, kind)?;
+        format_version = header.format_version;
+        value = value
+            .get_mut("data")
+            .map(Value::take)
+            .unwrap_or(Value::Null);
+    }
+    let value = migrate_json(kind, value, format_version)?;
+    serde_json::from_value(value).map_err(|e| ExportError::Parse(e.to_string()))
+}
+// Binary exports aren't self describing, so they have to match the current format exactly
+pub fn import_binary<T: DeserializeOwned>(kind: ExportKind, data: &[u8]) -> Result<T, ExportError> {
+    let header: ExportHeader = bincode::deserialize(data).map_err(|_| ExportError::NotVersioned)?;
+    check_header(&header, kind)?;
+    if header.precision != EXPORT_PRECISION {
+        return Err(ExportError::WrongPrecision {
+            found: header.precision,
+        });
+    }
+    if header.format_version != EXPORT_FORMAT_VERSION {
+        return Err(ExportError::Migration {
+            from_version: header.format_version,
+            reason: "binary exports can't be migrated, import a json export instead".to_string(),
+        });
+    }
+    // Same layout as the export envelope
+    let (_, data): (ExportHeader, T) =
+        bincode::deserialize(data).map_err(|e| ExportError::Parse(e.to_string()))?;
+    Ok(data)
+}
+fn migrate_json(
+    kind: ExportKind,
+    mut value: Value,
+    from_version: u32,
+) -> Result<Value, ExportError> {
+    let mut version = from_version;
+    while version < EXPORT_FORMAT_VERSION {
+        value = match version {
+            0 => migrate_json_v0(kind, value),
+            _ => Err("no migration available".to_string()),
+        }
+        .map_err(|reason| ExportError::Migration {
+            from_version: version,
+            reason,
+        })?;
+        version += 1;
+    }
+    Ok(value)
+}
+// Version 0 exports had no header, spaces had no fluids and didn't store their rid.
+// Fields added since then have defaults.
+fn migrate_json_v0(kind: ExportKind, mut value: Value) -> Result<Value, String> {
+    if kind != ExportKind::Space {
+        return Ok(value);
+    }
+    let Some(space_export) = value.as_object_mut() else {
+        return Err("space export is not an object".to_string());
+    };
+    space_export
+        .entry("liquid_world")
+        .or_insert(Value::Array(Vec::new()));
+    space_export
+        .entry("fluids")
+        .or_insert(Value::Array(Vec::new()));
+    let Some(space) = space_export.get_mut("space").and_then(Value::as_object_mut) else {
+        return Err("space export has no space".to_string());
+    };
+    if !space.contains_key("rid") {
+        let rid = serde_json::to_value(Rid::Invalid).map_err(|e| e.to_string())?;
+        space.insert("rid".to_string(), rid);
+    }
+    Ok(value)
+}
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    #[test]
+    fn migrate_json_current_version_is_unchanged() {
+        let value = json!({"space": {"rid": 1}});
+        let migrated =
+            migrate_json(ExportKind::Space, value.clone(), EXPORT_FORMAT_VERSION).unwrap();
+        assert_eq!(migrated, value);
+    }
+    #[test]
+    fn migrate_json_v0_space_gets_fluids_and_rid() {
+        let migrated = migrate_json(ExportKind::Space, json!({"space": {}}), 0).unwrap();
+        assert_eq!(migrated["liquid_world"], json!([]));
+        assert_eq!(migrated["fluids"], json!([]));
+        assert_eq!(
+            migrated["space"]["rid"],
+            serde_json::to_value(Rid::Invalid).unwrap()
+        );
+    }
+    #[test]
+    fn migrate_json_v0_space_without_space_fails() {
+        let error = migrate_json(ExportKind::Space, json!({}), 0).unwrap_err();
+        assert!(matches!(
+            error,
+            ExportError::Migration {
+                from_version: 0,
+                ..
+            }
+        ));
+    }
+    #[test]
+    fn migrate_json_v0_other_kinds_are_unchanged() {
+        let value = json!([{"rid": 1}]);
+        let migrated = migrate_json(ExportKind::Shapes, value.clone(), 0).unwrap();
+        assert_eq!(migrated, value);
+    }
+    #[test]
+    fn import_json_rejects_newer_versions() {
+        let mut header = ExportHeader::new(ExportKind::Shapes);
+        header.format_version = EXPORT_FORMAT_VERSION + 1;
+        let data = json!({"header": header, "data": []}).to_string();
+        let error = import_json::<Value>(ExportKind::Shapes, &data).unwrap_err();
+        assert!(matches!(error, ExportError::UnsupportedVersion { .. }));
+    }
+    #[test]
+    fn import_json_rejects_other_kinds() {
+        let data = export_json(ExportKind::Shapes, &json!([]));
+        let error = import_json::<Value>(ExportKind::Joints, &data).unwrap_err();
+        assert!(matches!(error, ExportError::WrongKind { .. }));
+    }
+    #[test]
+    fn import_json_round_trips() {
+        let data = export_json(ExportKind::Shape, &json!({"size": 2}));
+        let value = import_json::<Value>(ExportKind::Shape, &data).unwrap();
+        assert_eq!(value, json!({"size": 2}));
+    }
+}
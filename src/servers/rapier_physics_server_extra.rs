@@ -4,16 +4,51 @@ use godot::prelude::*;
 use hashbrown::HashMap;
 
 use crate::bodies::rapier_collision_object::IRapierCollisionObject;
+use crate::bodies::rapier_collision_object::RapierCollisionObject;
 use crate::fluids::rapier_fluid::RapierFluid;
 use crate::joints::rapier_joint::IRapierJoint;
 use crate::rapier_wrapper::handle::WorldHandle;
+use crate::rapier_wrapper::prelude::vector_to_godot;
+use crate::rapier_wrapper::prelude::vector_to_rapier;
+use crate::rapier_wrapper::prelude::CharacterMoveOptions;
 use crate::rapier_wrapper::prelude::PhysicsEngine;
+#[cfg(feature = "serde-serialize")]
+use crate::servers::rapier_physics_server_export::*;
+use crate::servers::rapier_physics_server_record::replay_calls;
 use crate::servers::RapierPhysicsServer;
+#[cfg(feature = "dim3")]
+use crate::shapes::rapier_cone_shape_3d::RapierConeShape3D;
+use crate::shapes::rapier_round_convex_polygon_shape::RapierRoundConvexPolygonShape;
+use crate::shapes::rapier_round_cuboid_shape::RapierRoundCuboidShape;
+#[cfg(feature = "dim3")]
+use crate::shapes::rapier_segment_shape_3d::RapierSegmentShape3D;
 use crate::shapes::rapier_shape::IRapierShape;
+use crate::shapes::rapier_shape::RapierShapeBase;
+use crate::shapes::rapier_triangle_shape::RapierTriangleShape;
+use crate::shapes::rapier_voxel_shape::RapierVoxelShape;
 use crate::spaces::rapier_space::RapierSpace;
 use crate::types::*;
 pub enum RapierBodyParam {
     ContactSkin = 0,
+    Dominance = 1,
+    FrictionCombineRule = 2,
+    RestitutionCombineRule = 3,
+    SolverMembership = 4,
+    SolverFilter = 5,
+}
+pub enum RapierShapeParam {
+    MeshFlags = 0,
+    BorderRadius = 1,
+    ConvexDecomposition = 2,
+}
+// Parry shapes that Godot has no shape type for
+pub enum RapierShapeType {
+    Cone = 0,
+    Triangle = 1,
+    RoundCuboid = 2,
+    RoundConvexPolygon = 3,
+    Segment = 4,
+    Voxels = 5,
 }
 pub type PhysicsShapes = HashMap<Rid, Box<dyn IRapierShape>>;
 pub type PhysicsSpaces = HashMap<Rid, RapierSpace>;
@@ -35,10 +70,66 @@ impl From<i32> for RapierBodyParam {
     fn from(i: i32) -> Self {
         match i {
             0 => RapierBodyParam::ContactSkin,
+            1 => RapierBodyParam::Dominance,
+            2 => RapierBodyParam::FrictionCombineRule,
+            3 => RapierBodyParam::RestitutionCombineRule,
+            4 => RapierBodyParam::SolverMembership,
+            5 => RapierBodyParam::SolverFilter,
             _ => RapierBodyParam::ContactSkin,
         }
     }
 }
+impl From<i32> for RapierShapeParam {
+    fn from(i: i32) -> Self {
+        match i {
+            0 => RapierShapeParam::MeshFlags,
+            1 => RapierShapeParam::BorderRadius,
+            2 => RapierShapeParam::ConvexDecomposition,
+            _ => RapierShapeParam::MeshFlags,
+        }
+    }
+}
+impl RapierShapeType {
+    fn from_i32(i: i32) -> Option<Self> {
+        match i {
+            0 => Some(RapierShapeType::Cone),
+            1 => Some(RapierShapeType::Triangle),
+            2 => Some(RapierShapeType::RoundCuboid),
+            3 => Some(RapierShapeType::RoundConvexPolygon),
+            4 => Some(RapierShapeType::Segment),
+            5 => Some(RapierShapeType::Voxels),
+            _ => None,
+        }
+    }
+}
+// Lengths are in godot units and angles in radians. Up defaults to the godot up direction
+fn character_move_options(options: &Dictionary) -> CharacterMoveOptions {
+    let get_real = |key: &str| {
+        options
+            .get(key)
+            .and_then(|value| value.try_to::<real>().ok())
+    };
+    let get_bool = |key: &str| {
+        options
+            .get(key)
+            .and_then(|value| value.try_to::<bool>().ok())
+    };
+    let up = options
+        .get("up")
+        .and_then(|value| value.try_to::<Vector>().ok())
+        .unwrap_or(Vector::UP);
+    CharacterMoveOptions {
+        up: Some(vector_to_rapier(up)),
+        offset: get_real("offset"),
+        slide: get_bool("slide"),
+        autostep_max_height: get_real("autostep_max_height"),
+        autostep_min_width: get_real("autostep_min_width"),
+        autostep_include_dynamic_bodies: get_bool("autostep_include_dynamic_bodies"),
+        max_slope_climb_angle: get_real("max_slope_climb_angle"),
+        min_slope_slide_angle: get_real("min_slope_slide_angle"),
+        snap_to_ground: get_real("snap_to_ground"),
+    }
+}
 #[godot_api]
 impl RapierPhysicsServer {
     #[func]
@@ -48,6 +139,12 @@ impl RapierPhysicsServer {
         else {
             return;
         };
+        physics_singleton
+            .bind_mut()
+            .implementation
+            .record("body_set_extra_param", || {
+                vec![body.to_variant(), param.to_variant(), value.clone()]
+            });
         let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
         if let Some(body) = physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
@@ -76,6 +173,304 @@ impl RapierPhysicsServer {
         0.0.to_variant()
     }
 
+    #[func]
+    fn body_merge_rectangle_shapes(body: Rid) -> i32 {
+        let Ok(mut physics_singleton) =
+            PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
+        else {
+            return 0;
+        };
+        physics_singleton
+            .bind_mut()
+            .implementation
+            .record("body_merge_rectangle_shapes", || vec![body.to_variant()]);
+        let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
+        if let Some(body) = physics_data.collision_objects.get_mut(&body)
+            && let Some(body) = body.get_mut_body()
+        {
+            return body.merge_rectangle_shapes(
+                &mut physics_data.physics_engine,
+                &mut physics_data.shapes,
+                &mut physics_data.spaces,
+            );
+        }
+        0
+    }
+
+    #[func]
+    fn character_move(body: Rid, desired_translation: Vector, options: Dictionary) -> Dictionary {
+        let Ok(mut physics_singleton) =
+            PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
+        else {
+            return Dictionary::new();
+        };
+        let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
+        let Some(body) = physics_data
+            .collision_objects
+            .get(&body)
+            .and_then(|body| body.get_body())
+        else {
+            godot_error!("Character body not found");
+            return Dictionary::new();
+        };
+        let Some(space) = physics_data.spaces.get(&body.get_base().get_space()) else {
+            godot_error!("Character body is not in a space");
+            return Dictionary::new();
+        };
+        let movement = space.character_move(
+            body,
+            desired_translation,
+            &character_move_options(&options),
+            &physics_data.physics_engine,
+            &physics_data.shapes,
+            &physics_data.collision_objects,
+        );
+        let mut collisions = Array::<Dictionary>::new();
+        for collision in movement.collisions {
+            let position = vector_to_godot(collision.pixel_position);
+            let (collider, mut collider_shape) =
+                RapierCollisionObject::get_collider_user_data(&collision.user_data);
+            let mut collider_id = 0;
+            if let Some(collision_object) = physics_data.collision_objects.get(&collider) {
+                collider_shape = collision_object
+                    .get_base()
+                    .get_shape_index_at(collider_shape, position);
+                collider_id = collision_object.get_base().get_instance_id();
+            }
+            collisions.push(dict! {
+                "position": position,
+                "normal": vector_to_godot(collision.normal),
+                "collider": collider,
+                "collider_id": collider_id as i64,
+                "collider_shape": collider_shape as i64,
+                "translation_applied": vector_to_godot(collision.translation_applied),
+                "translation_remaining": vector_to_godot(collision.translation_remaining),
+            });
+        }
+        dict! {
+            "translation": vector_to_godot(movement.translation),
+            "grounded": movement.grounded,
+            "is_sliding_down_slope": movement.is_sliding_down_slope,
+            "collisions": collisions,
+        }
+    }
+
+    #[func]
+    fn custom_shape_create_rapier(shape_type: i32, data: Variant) -> Rid {
+        let Ok(mut physics_singleton) =
+            PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
+        else {
+            return Rid::Invalid;
+        };
+        let rid = rid_from_int64(rid_allocate_id());
+        physics_singleton
+            .bind_mut()
+            .implementation
+            .record_with_result(
+                "custom_shape_create_rapier",
+                || vec![shape_type.to_variant(), data.clone()],
+                rid,
+            );
+        let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
+        let mut shape: Box<dyn IRapierShape> = match RapierShapeType::from_i32(shape_type) {
+            #[cfg(feature = "dim3")]
+            Some(RapierShapeType::Cone) => Box::new(RapierConeShape3D::new(rid)),
+            Some(RapierShapeType::Triangle) => Box::new(RapierTriangleShape::new(rid)),
+            Some(RapierShapeType::RoundCuboid) => Box::new(RapierRoundCuboidShape::new(rid)),
+            Some(RapierShapeType::RoundConvexPolygon) => {
+                Box::new(RapierRoundConvexPolygonShape::new(rid))
+            }
+            #[cfg(feature = "dim3")]
+            Some(RapierShapeType::Segment) => Box::new(RapierSegmentShape3D::new(rid)),
+            Some(RapierShapeType::Voxels) => Box::new(RapierVoxelShape::new(rid)),
+            #[cfg(feature = "dim2")]
+            Some(RapierShapeType::Cone) | Some(RapierShapeType::Segment) => {
+                godot_error!("Shape type {} is only supported in 3D", shape_type);
+                return Rid::Invalid;
+            }
+            None => {
+                godot_error!("Invalid shape type {}", shape_type);
+                return Rid::Invalid;
+            }
+        };
+        shape.set_data(data, &mut physics_data.physics_engine);
+        physics_data.shapes.insert(rid, shape);
+        rid
+    }
+
+    #[func]
+    fn shape_set_extra_param(shape: Rid, param: i32, value: Variant) {
+        let Ok(mut physics_singleton) =
+            PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
+        else {
+            return;
+        };
+        physics_singleton
+            .bind_mut()
+            .implementation
+            .record("shape_set_extra_param", || {
+                vec![shape.to_variant(), param.to_variant(), value.clone()]
+            });
+        let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
+        let mut owners = None;
+        if let Some(rapier_shape) = physics_data.shapes.get_mut(&shape) {
+            let param = RapierShapeParam::from(param);
+            if matches!(param, RapierShapeParam::BorderRadius)
+                && rapier_shape.has_data_border_radius()
+            {
+                godot_error!("The border radius of round shapes is set in their data");
+                return;
+            }
+            rapier_shape.get_mut_base().set_extra_param(param, value);
+            if rapier_shape.get_base().is_valid() {
+                let data = rapier_shape.get_data();
+                rapier_shape.set_data(data, &mut physics_data.physics_engine);
+                owners = Some(rapier_shape.get_base().get_owners().clone());
+            }
+        }
+        if let Some(owners) = owners {
+            RapierShapeBase::call_shape_changed(owners, shape, physics_data);
+        }
+    }
+
+    #[func]
+    fn voxel_shape_set_cells(shape: Rid, cells: Array<Vectori>, occupied: bool) {
+        let Ok(mut physics_singleton) =
+            PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
+        else {
+            return;
+        };
+        physics_singleton
+            .bind_mut()
+            .implementation
+            .record("voxel_shape_set_cells", || {
+                vec![
+                    shape.to_variant(),
+                    cells.to_variant(),
+                    occupied.to_variant(),
+                ]
+            });
+        let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
+        let Some(rapier_shape) = physics_data.shapes.get_mut(&shape) else {
+            return;
+        };
+        let Some(voxels) = rapier_shape.get_mut_voxels() else {
+            godot_error!("Shape is not a voxel shape");
+            return;
+        };
+        let cells: Vec<Vectori> = cells.iter_shared().collect();
+        let chunk_indices = voxels.set_cells(&cells, occupied, &mut physics_data.physics_engine);
+        if chunk_indices.is_empty() {
+            return;
+        }
+        let owners = rapier_shape.get_base().get_owners().clone();
+        for (owner, _) in owners {
+            if let Some(owner) = physics_data.collision_objects.get_mut(&owner) {
+                RapierCollisionObject::shape_chunks_changed(
+                    owner.as_mut(),
+                    shape,
+                    &chunk_indices,
+                    &mut physics_data.physics_engine,
+                    &mut physics_data.shapes,
+                    &mut physics_data.spaces,
+                );
+            }
+        }
+    }
+
+    #[func]
+    fn shape_get_extra_param(shape: Rid, param: i32) -> Variant {
+        let Ok(mut physics_singleton) =
+            PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
+        else {
+            return 0.to_variant();
+        };
+        let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
+        if let Some(shape) = physics_data.shapes.get(&shape) {
+            return shape
+                .get_base()
+                .get_extra_param(RapierShapeParam::from(param));
+        }
+        0.to_variant()
+    }
+
+    #[func]
+    fn shapes_get_dedup_stats() -> Dictionary {
+        let Ok(mut physics_singleton) =
+            PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
+        else {
+            return Dictionary::new();
+        };
+        let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
+        let stats = physics_data.physics_engine.get_shape_dedup_stats();
+        dict! {
+            "unique_shapes": stats.unique_shapes as i64,
+            "shape_references": stats.shape_references as i64,
+            "deduplicated_shapes": (stats.shape_references - stats.unique_shapes) as i64,
+        }
+    }
+
+    #[func]
+    fn space_get_state_hash(space: Rid) -> i64 {
+        let Ok(mut physics_singleton) =
+            PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
+        else {
+            return 0;
+        };
+        let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
+        if let Some(space) = physics_data.spaces.get(&space) {
+            return physics_data
+                .physics_engine
+                .world_get_state_hash(space.get_handle()) as i64;
+        }
+        0
+    }
+
+    #[func]
+    fn body_get_state_hash(body: Rid) -> i64 {
+        let Ok(mut physics_singleton) =
+            PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
+        else {
+            return 0;
+        };
+        let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
+        if let Some(body) = physics_data.collision_objects.get(&body) {
+            return physics_data.physics_engine.body_get_state_hash(
+                body.get_base().get_space_handle(),
+                body.get_base().get_body_handle(),
+            ) as i64;
+        }
+        0
+    }
+
+    #[func]
+    fn start_recording(path: GString) -> bool {
+        let Ok(mut physics_singleton) =
+            PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
+        else {
+            return false;
+        };
+        physics_singleton
+            .bind_mut()
+            .implementation
+            .start_recording(path)
+    }
+
+    #[func]
+    fn stop_recording() {
+        let Ok(mut physics_singleton) =
+            PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
+        else {
+            return;
+        };
+        physics_singleton.bind_mut().implementation.stop_recording();
+    }
+
+    #[func]
+    fn replay_recording(path: GString) -> bool {
+        replay_calls(path)
+    }
+
     #[cfg(feature = "serde-serialize")]
     #[func]
     fn joints_export_json() -> String {
@@ -86,13 +481,7 @@ impl RapierPhysicsServer {
         };
         let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
         let values = physics_data.joints.values().clone().collect::<Vec<_>>();
-        match serde_json::to_string_pretty(&values) {
-            Ok(s) => s,
-            Err(err) => {
-                godot_error!("{}", err);
-                "{}".to_string()
-            }
-        }
+        export_json(ExportKind::Joints, &values)
     }
 
     #[cfg(feature = "serde-serialize")]
@@ -105,15 +494,7 @@ impl RapierPhysicsServer {
         };
         let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
         if let Some(joint) = physics_data.joints.get(&joint) {
-            match serde_json::to_string_pretty(&joint) {
-                Ok(s) => {
-                    return s;
-                }
-                Err(err) => {
-                    godot_error!("{}", err);
-                    return "{}".to_string();
-                }
-            }
+            return export_json(ExportKind::Joint, joint);
         }
         "{}".to_string()
     }
@@ -128,13 +509,7 @@ impl RapierPhysicsServer {
         };
         let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
         let values = physics_data.shapes.values().clone().collect::<Vec<_>>();
-        match serde_json::to_string_pretty(&values) {
-            Ok(s) => s,
-            Err(err) => {
-                godot_error!("{}", err);
-                "{}".to_string()
-            }
-        }
+        export_json(ExportKind::Shapes, &values)
     }
 
     #[cfg(feature = "serde-serialize")]
@@ -147,15 +522,7 @@ impl RapierPhysicsServer {
         };
         let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
         if let Some(shape) = physics_data.shapes.get(&shape) {
-            match serde_json::to_string_pretty(&shape) {
-                Ok(s) => {
-                    return s;
-                }
-                Err(err) => {
-                    godot_error!("{}", err);
-                    return "{}".to_string();
-                }
-            }
+            return export_json(ExportKind::Shape, shape);
         }
         "{}".to_string()
     }
@@ -170,11 +537,109 @@ impl RapierPhysicsServer {
         };
         let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
         if let Some(space) = physics_data.spaces.get(&space) {
-            return space.export_json(&mut physics_data.physics_engine);
+            return space.export_json(&mut physics_data.physics_engine, &physics_data.fluids);
         }
         "{}".to_string()
     }
 
+    #[cfg(feature = "serde-serialize")]
+    #[func]
+    fn save_state() -> PackedByteArray {
+        let Ok(physics_singleton) = PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
+        else {
+            return PackedByteArray::new();
+        };
+        let physics_data = &physics_singleton.bind().implementation.physics_data;
+        physics_data.save_state()
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    #[func]
+    fn load_state(data: PackedByteArray) {
+        let Ok(mut physics_singleton) =
+            PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
+        else {
+            return;
+        };
+        let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
+        physics_data.load_state(data);
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    #[func]
+    fn space_export_delta(space: Rid, consumer_id: i64, baseline_id: i64) -> PackedByteArray {
+        let Ok(mut physics_singleton) =
+            PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
+        else {
+            return PackedByteArray::new();
+        };
+        let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
+        if let Some(space) = physics_data.spaces.get(&space) {
+            return space.export_delta(
+                &mut physics_data.physics_engine,
+                consumer_id as u64,
+                baseline_id as u64,
+            );
+        }
+        PackedByteArray::new()
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    #[func]
+    fn space_remove_delta_consumer(space: Rid, consumer_id: i64) {
+        let Ok(mut physics_singleton) =
+            PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
+        else {
+            return;
+        };
+        let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
+        if let Some(space) = physics_data.spaces.get(&space) {
+            physics_data
+                .physics_engine
+                .world_remove_delta_consumer(space.get_handle(), consumer_id as u64);
+        }
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    #[func]
+    fn space_apply_delta(space: Rid, delta: PackedByteArray) -> i64 {
+        let Ok(mut physics_singleton) =
+            PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
+        else {
+            return 0;
+        };
+        let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
+        if let Some(space) = physics_data.spaces.get(&space) {
+            return space.apply_delta(&mut physics_data.physics_engine, delta) as i64;
+        }
+        0
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    #[func]
+    fn space_import_json(space: Rid, data: String) {
+        let Ok(mut physics_singleton) =
+            PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
+        else {
+            return;
+        };
+        let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
+        RapierSpace::import_json(&space, physics_data, data);
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    #[func]
+    fn space_import_binary(space: Rid, data: PackedByteArray) {
+        let Ok(mut physics_singleton) =
+            PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
+        else {
+            return;
+        };
+        let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
+        RapierSpace::import_binary(&space, physics_data, data);
+    }
+
+    #[cfg(feature = "serde-serialize")]
     #[func]
     fn collision_objects_export_json() -> String {
         let Ok(mut physics_singleton) =
@@ -183,29 +648,41 @@ impl RapierPhysicsServer {
             return "{}".to_string();
         };
         let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
-        let _values = physics_data
+        let values = physics_data
             .collision_objects
             .values()
             .clone()
             .collect::<Vec<_>>();
-        //match serde_json::to_string_pretty(&values) {
-        //    Ok(s) => s,
-        //    Err(err) => {
-        //        godot_error!("{}", err);
-        //        "{}".to_string()
-        //    }
-        //}
-        "{}".to_string()
+        export_json(ExportKind::CollisionObjects, &values)
     }
 
+    #[cfg(feature = "serde-serialize")]
     #[func]
-    fn collision_object_export_json(_collision_object: Rid) -> String {
+    fn collision_object_export_json(collision_object: Rid) -> String {
         let Ok(mut physics_singleton) =
             PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
         else {
             return "{}".to_string();
         };
-        let _physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
+        let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
+        if let Some(collision_object) = physics_data.collision_objects.get(&collision_object) {
+            return export_json(ExportKind::CollisionObject, collision_object);
+        }
+        "{}".to_string()
+    }
+
+    // Without serialization the exports are empty, same as for a missing object
+    #[cfg(not(feature = "serde-serialize"))]
+    #[func]
+    fn collision_objects_export_json() -> String {
+        godot_error!("Collision objects can't be exported without the serde-serialize feature");
+        "{}".to_string()
+    }
+
+    #[cfg(not(feature = "serde-serialize"))]
+    #[func]
+    fn collision_object_export_json(_collision_object: Rid) -> String {
+        godot_error!("Collision objects can't be exported without the serde-serialize feature");
         "{}".to_string()
     }
 
@@ -216,8 +693,16 @@ impl RapierPhysicsServer {
         else {
             return Rid::Invalid;
         };
-        let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
         let rid = rid_from_int64(rid_allocate_id());
+        physics_singleton
+            .bind_mut()
+            .implementation
+            .record_with_result(
+                "custom_shape_create_rapier",
+                || vec![shape_type.to_variant(), data.clone()],
+                rid,
+            );
+        let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
         let fluid = RapierFluid::new(rid);
         physics_data.fluids.insert(rid, fluid);
         rid
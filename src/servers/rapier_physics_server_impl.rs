@@ -9,6 +9,7 @@ use godot::engine::utilities::rid_from_int64;
 use godot::prelude::*;
 
 use super::rapier_physics_server_extra::PhysicsData;
+use super::rapier_physics_server_record::ServerRecorder;
 use super::rapier_project_settings::RapierProjectSettings;
 use crate::bodies::rapier_area::RapierArea;
 use crate::bodies::rapier_body::RapierBody;
@@ -26,7 +27,11 @@ use crate::shapes::rapier_capsule_shape::RapierCapsuleShape;
 use crate::shapes::rapier_circle_shape::RapierCircleShape;
 #[cfg(feature = "dim2")]
 use crate::shapes::rapier_concave_polygon_shape_2d::RapierConcavePolygonShape2D;
+#[cfg(feature = "dim3")]
+use crate::shapes::rapier_concave_polygon_shape_3d::RapierConcavePolygonShape3D;
 use crate::shapes::rapier_convex_polygon_shape::RapierConvexPolygonShape;
+#[cfg(feature = "dim3")]
+use crate::shapes::rapier_heightmap_shape_3d::RapierHeightMapShape3D;
 use crate::shapes::rapier_rectangle_shape::RapierRectangleShape;
 #[cfg(feature = "dim2")]
 use crate::shapes::rapier_segment_shape_2d::RapierSegmentShape2D;
@@ -47,10 +52,14 @@ pub struct RapierPhysicsServerImpl {
     num_additional_friction_iterations: usize,
     num_internal_pgs_iterations: usize,
     num_solver_iterations: usize,
+    recorder: Option<ServerRecorder>,
     pub physics_data: PhysicsData,
 }
 impl RapierPhysicsServerImpl {
     pub(super) fn default() -> Self {
+        let mut physics_data = PhysicsData::default();
+        physics_data.physics_engine.shape_subdivisions =
+            RapierProjectSettings::get_shape_subdivisions().max(3) as u32;
         Self {
             active: true,
             flushing_queries: false,
@@ -66,7 +75,37 @@ impl RapierPhysicsServerImpl {
                 RapierProjectSettings::get_solver_num_internal_pgs_iterations() as usize,
             num_solver_iterations: RapierProjectSettings::get_solver_num_solver_iterations()
                 as usize,
-            physics_data: PhysicsData::default(),
+            recorder: None,
+            physics_data,
+        }
+    }
+
+    pub fn start_recording(&mut self, path: GString) -> bool {
+        self.recorder = ServerRecorder::new(path);
+        self.recorder.is_some()
+    }
+
+    pub fn stop_recording(&mut self) {
+        self.recorder = None;
+    }
+
+    // Callbacks aren't recorded, as they point to objects that won't exist when replaying
+    pub(super) fn record(&mut self, method: &str, args: impl FnOnce() -> Vec<Variant>) {
+        self.record_with_result(method, args, Rid::Invalid);
+    }
+
+    pub(super) fn record_create(&mut self, method: &str, rid: Rid) {
+        self.record_with_result(method, Vec::new, rid);
+    }
+
+    pub(super) fn record_with_result(
+        &mut self,
+        method: &str,
+        args: impl FnOnce() -> Vec<Variant>,
+        result: Rid,
+    ) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(method, args(), result);
         }
     }
 
@@ -74,6 +113,7 @@ impl RapierPhysicsServerImpl {
         let rid = rid_from_int64(rid_allocate_id());
         let shape = RapierWorldBoundaryShape::new(rid);
         self.physics_data.shapes.insert(rid, Box::new(shape));
+        self.record_create("world_boundary_shape_create", rid);
         rid
     }
 
@@ -81,6 +121,7 @@ impl RapierPhysicsServerImpl {
         let rid = rid_from_int64(rid_allocate_id());
         let shape = RapierSeparationRayShape::new(rid);
         self.physics_data.shapes.insert(rid, Box::new(shape));
+        self.record_create("separation_ray_shape_create", rid);
         rid
     }
 
@@ -89,6 +130,7 @@ impl RapierPhysicsServerImpl {
         let rid = rid_from_int64(rid_allocate_id());
         let shape = RapierSegmentShape2D::new(rid);
         self.physics_data.shapes.insert(rid, Box::new(shape));
+        self.record_create("segment_shape_create", rid);
         rid
     }
 
@@ -96,6 +138,10 @@ impl RapierPhysicsServerImpl {
         let rid = rid_from_int64(rid_allocate_id());
         let shape = RapierCircleShape::new(rid);
         self.physics_data.shapes.insert(rid, Box::new(shape));
+        #[cfg(feature = "dim2")]
+        self.record_create("circle_shape_create", rid);
+        #[cfg(feature = "dim3")]
+        self.record_create("sphere_shape_create", rid);
         rid
     }
 
@@ -103,6 +149,10 @@ impl RapierPhysicsServerImpl {
         let rid = rid_from_int64(rid_allocate_id());
         let shape = RapierRectangleShape::new(rid);
         self.physics_data.shapes.insert(rid, Box::new(shape));
+        #[cfg(feature = "dim2")]
+        self.record_create("rectangle_shape_create", rid);
+        #[cfg(feature = "dim3")]
+        self.record_create("box_shape_create", rid);
         rid
     }
 
@@ -110,6 +160,7 @@ impl RapierPhysicsServerImpl {
         let rid = rid_from_int64(rid_allocate_id());
         let shape = RapierCapsuleShape::new(rid);
         self.physics_data.shapes.insert(rid, Box::new(shape));
+        self.record_create("capsule_shape_create", rid);
         rid
     }
 
@@ -117,6 +168,7 @@ impl RapierPhysicsServerImpl {
         let rid = rid_from_int64(rid_allocate_id());
         let shape = RapierConvexPolygonShape::new(rid);
         self.physics_data.shapes.insert(rid, Box::new(shape));
+        self.record_create("convex_polygon_shape_create", rid);
         rid
     }
 
@@ -125,20 +177,32 @@ impl RapierPhysicsServerImpl {
         let rid = rid_from_int64(rid_allocate_id());
         let shape = RapierConcavePolygonShape2D::new(rid);
         self.physics_data.shapes.insert(rid, Box::new(shape));
+        self.record_create("concave_polygon_shape_create", rid);
         rid
     }
 
     #[cfg(feature = "dim3")]
     pub(super) fn concave_polygon_shape_create(&mut self) -> Rid {
-        Rid::Invalid
+        let rid = rid_from_int64(rid_allocate_id());
+        let shape = RapierConcavePolygonShape3D::new(rid);
+        self.physics_data.shapes.insert(rid, Box::new(shape));
+        self.record_create("concave_polygon_shape_create", rid);
+        rid
     }
 
     #[cfg(feature = "dim3")]
     pub(super) fn heightmap_shape_create(&mut self) -> Rid {
-        Rid::Invalid
+        let rid = rid_from_int64(rid_allocate_id());
+        let shape = RapierHeightMapShape3D::new(rid);
+        self.physics_data.shapes.insert(rid, Box::new(shape));
+        self.record_create("heightmap_shape_create", rid);
+        rid
     }
 
     pub(super) fn shape_set_data(&mut self, shape: Rid, data: Variant) {
+        self.record("shape_set_data", || {
+            vec![shape.to_variant(), data.to_variant()]
+        });
         let mut owners = None;
         if let Some(shape) = self.physics_data.shapes.get_mut(&shape) {
             shape.set_data(data, &mut self.physics_data.physics_engine);
@@ -219,10 +283,14 @@ impl RapierPhysicsServerImpl {
         let rid = rid_from_int64(rid_allocate_id());
         let space = RapierSpace::new(rid, &mut self.physics_data.physics_engine);
         self.physics_data.spaces.insert(rid, space);
+        self.record_create("space_create", rid);
         rid
     }
 
     pub(super) fn space_set_active(&mut self, space_rid: Rid, active: bool) {
+        self.record("space_set_active", || {
+            vec![space_rid.to_variant(), active.to_variant()]
+        });
         if let Some(space) = self.physics_data.spaces.get(&space_rid) {
             if active {
                 self.physics_data
@@ -261,6 +329,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn space_set_debug_contacts(&mut self, space: Rid, max_contacts: i32) {
+        self.record("space_set_debug_contacts", || {
+            vec![space.to_variant(), max_contacts.to_variant()]
+        });
         if let Some(space) = self.physics_data.spaces.get_mut(&space) {
             space.set_debug_contacts(max_contacts);
         }
@@ -286,10 +357,14 @@ impl RapierPhysicsServerImpl {
         self.physics_data
             .collision_objects
             .insert(rid, Box::new(area));
+        self.record_create("area_create", rid);
         rid
     }
 
     pub(super) fn area_set_space(&mut self, area: Rid, space: Rid) {
+        self.record("area_set_space", || {
+            vec![area.to_variant(), space.to_variant()]
+        });
         RapierArea::clear_detected_bodies(
             &area,
             &mut self.physics_data.spaces,
@@ -319,6 +394,14 @@ impl RapierPhysicsServerImpl {
         transform: Transform,
         disabled: bool,
     ) {
+        self.record("area_add_shape", || {
+            vec![
+                area.to_variant(),
+                shape.to_variant(),
+                transform.to_variant(),
+                disabled.to_variant(),
+            ]
+        });
         if let Some(area) = self.physics_data.collision_objects.get_mut(&area) {
             area.add_shape(
                 shape,
@@ -332,6 +415,13 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn area_set_shape(&mut self, area: Rid, shape_idx: i32, shape: Rid) {
+        self.record("area_set_shape", || {
+            vec![
+                area.to_variant(),
+                shape_idx.to_variant(),
+                shape.to_variant(),
+            ]
+        });
         if let Some(area) = self.physics_data.collision_objects.get_mut(&area) {
             area.set_shape(
                 shape_idx as usize,
@@ -349,6 +439,13 @@ impl RapierPhysicsServerImpl {
         shape_idx: i32,
         transform: Transform,
     ) {
+        self.record("area_set_shape_transform", || {
+            vec![
+                area.to_variant(),
+                shape_idx.to_variant(),
+                transform.to_variant(),
+            ]
+        });
         if let Some(area) = self.physics_data.collision_objects.get_mut(&area) {
             area.set_shape_transform(
                 shape_idx as usize,
@@ -361,6 +458,13 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn area_set_shape_disabled(&mut self, area: Rid, shape_idx: i32, disabled: bool) {
+        self.record("area_set_shape_disabled", || {
+            vec![
+                area.to_variant(),
+                shape_idx.to_variant(),
+                disabled.to_variant(),
+            ]
+        });
         if let Some(area) = self.physics_data.collision_objects.get_mut(&area) {
             area.set_shape_disabled(
                 shape_idx as usize,
@@ -394,6 +498,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn area_remove_shape(&mut self, area: Rid, shape_idx: i32) {
+        self.record("area_remove_shape", || {
+            vec![area.to_variant(), shape_idx.to_variant()]
+        });
         if let Some(area) = self.physics_data.collision_objects.get_mut(&area) {
             area.remove_shape_idx(
                 shape_idx as usize,
@@ -405,6 +512,7 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn area_clear_shapes(&mut self, area: Rid) {
+        self.record("area_clear_shapes", || vec![area.to_variant()]);
         if let Some(area) = self.physics_data.collision_objects.get_mut(&area) {
             while area.get_base().get_shape_count() > 0 {
                 area.remove_shape_idx(
@@ -418,6 +526,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn area_attach_object_instance_id(&mut self, area: Rid, id: u64) {
+        self.record("area_attach_object_instance_id", || {
+            vec![area.to_variant(), (id as i64).to_variant()]
+        });
         if let Some(area) = self.physics_data.collision_objects.get_mut(&area) {
             area.get_mut_base().set_instance_id(id);
         }
@@ -431,6 +542,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn area_attach_canvas_instance_id(&mut self, area: Rid, id: u64) {
+        self.record("area_attach_canvas_instance_id", || {
+            vec![area.to_variant(), (id as i64).to_variant()]
+        });
         if let Some(area) = self.physics_data.collision_objects.get_mut(&area) {
             area.get_mut_base().set_canvas_instance_id(id);
         }
@@ -444,6 +558,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn area_set_param(&mut self, area: Rid, param: AreaParameter, value: Variant) {
+        self.record("area_set_param", || {
+            vec![area.to_variant(), param.to_variant(), value.to_variant()]
+        });
         if let Some(space) = self.physics_data.spaces.get_mut(&area) {
             space.set_default_area_param(param, value);
             return;
@@ -456,6 +573,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn area_set_transform(&mut self, area: Rid, transform: Transform) {
+        self.record("area_set_transform", || {
+            vec![area.to_variant(), transform.to_variant()]
+        });
         if let Some(area) = self.physics_data.collision_objects.get_mut(&area) {
             area.get_mut_base().set_transform(
                 transform,
@@ -485,6 +605,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn area_set_collision_layer(&mut self, area: Rid, layer: u32) {
+        self.record("area_set_collision_layer", || {
+            vec![area.to_variant(), layer.to_variant()]
+        });
         if let Some(area) = self.physics_data.collision_objects.get_mut(&area) {
             area.get_mut_base()
                 .set_collision_layer(layer, &mut self.physics_data.physics_engine);
@@ -499,6 +622,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn area_set_collision_mask(&mut self, area: Rid, mask: u32) {
+        self.record("area_set_collision_mask", || {
+            vec![area.to_variant(), mask.to_variant()]
+        });
         if let Some(area) = self.physics_data.collision_objects.get_mut(&area) {
             area.get_mut_base()
                 .set_collision_mask(mask, &mut self.physics_data.physics_engine);
@@ -513,6 +639,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn area_set_monitorable(&mut self, area: Rid, monitorable: bool) {
+        self.record("area_set_monitorable", || {
+            vec![area.to_variant(), monitorable.to_variant()]
+        });
         if let Some(area) = self.physics_data.collision_objects.get_mut(&area) {
             if let Some(area) = area.get_mut_area() {
                 area.set_monitorable(monitorable);
@@ -521,6 +650,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn area_set_pickable(&mut self, area: Rid, pickable: bool) {
+        self.record("area_set_pickable", || {
+            vec![area.to_variant(), pickable.to_variant()]
+        });
         if let Some(area) = self.physics_data.collision_objects.get_mut(&area) {
             area.get_mut_base().set_pickable(pickable);
         }
@@ -528,6 +660,9 @@ impl RapierPhysicsServerImpl {
 
     #[cfg(feature = "dim3")]
     pub(super) fn area_set_ray_pickable(&mut self, area: Rid, pickable: bool) {
+        self.record("area_set_ray_pickable", || {
+            vec![area.to_variant(), pickable.to_variant()]
+        });
         if let Some(area) = self.physics_data.collision_objects.get_mut(&area) {
             area.get_mut_base().set_pickable(pickable);
         }
@@ -555,10 +690,14 @@ impl RapierPhysicsServerImpl {
         self.physics_data
             .collision_objects
             .insert(rid, Box::new(body));
+        self.record_create("body_create", rid);
         rid
     }
 
     pub(super) fn body_set_space(&mut self, body: Rid, space: Rid) {
+        self.record("body_set_space", || {
+            vec![body.to_variant(), space.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             body.set_space(
                 space,
@@ -577,14 +716,32 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_set_mode(&mut self, body: Rid, mode: BodyMode) {
+        self.record("body_set_mode", || {
+            vec![body.to_variant(), mode.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body)
             && let Some(body) = body.get_mut_body()
         {
+            let was_dynamic = body.get_base().is_dynamic();
             body.set_mode(
                 mode,
                 &mut self.physics_data.physics_engine,
                 &mut self.physics_data.spaces,
             );
+            // Concave shapes switch between their convex decomposition and the original shape,
+            // and merged shapes are only kept for static bodies
+            if (was_dynamic != body.get_base().is_dynamic()
+                && body
+                    .get_base()
+                    .uses_convex_decomposition(&self.physics_data.shapes))
+                || (mode != BodyMode::STATIC && body.get_base().merged_shapes.is_some())
+            {
+                body.recreate_shapes(
+                    &mut self.physics_data.physics_engine,
+                    &mut self.physics_data.shapes,
+                    &mut self.physics_data.spaces,
+                );
+            }
         }
         RapierBody::apply_area_orverride_to_body(
             &body,
@@ -610,6 +767,14 @@ impl RapierPhysicsServerImpl {
         transform: Transform,
         disabled: bool,
     ) {
+        self.record("body_add_shape", || {
+            vec![
+                body.to_variant(),
+                shape.to_variant(),
+                transform.to_variant(),
+                disabled.to_variant(),
+            ]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.add_shape(
@@ -625,6 +790,13 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_set_shape(&mut self, body: Rid, shape_idx: i32, shape: Rid) {
+        self.record("body_set_shape", || {
+            vec![
+                body.to_variant(),
+                shape_idx.to_variant(),
+                shape.to_variant(),
+            ]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.set_shape(
@@ -644,6 +816,13 @@ impl RapierPhysicsServerImpl {
         shape_idx: i32,
         transform: Transform,
     ) {
+        self.record("body_set_shape_transform", || {
+            vec![
+                body.to_variant(),
+                shape_idx.to_variant(),
+                transform.to_variant(),
+            ]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.set_shape_transform(
@@ -685,6 +864,13 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_set_shape_disabled(&mut self, body: Rid, shape_idx: i32, disabled: bool) {
+        self.record("body_set_shape_disabled", || {
+            vec![
+                body.to_variant(),
+                shape_idx.to_variant(),
+                disabled.to_variant(),
+            ]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.set_shape_disabled(
@@ -705,6 +891,14 @@ impl RapierPhysicsServerImpl {
         enable: bool,
         margin: f32,
     ) {
+        self.record("body_set_shape_as_one_way_collision", || {
+            vec![
+                body.to_variant(),
+                shape_idx.to_variant(),
+                enable.to_variant(),
+                margin.to_variant(),
+            ]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             body.get_mut_base()
                 .set_shape_as_one_way_collision(shape_idx as usize, enable, margin);
@@ -712,6 +906,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_remove_shape(&mut self, body: Rid, shape_idx: i32) {
+        self.record("body_remove_shape", || {
+            vec![body.to_variant(), shape_idx.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.remove_shape_idx(
@@ -725,6 +922,7 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_clear_shapes(&mut self, body: Rid) {
+        self.record("body_clear_shapes", || vec![body.to_variant()]);
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             while body.get_base().get_shape_count() > 0 {
                 body.remove_shape_idx(
@@ -738,6 +936,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_attach_object_instance_id(&mut self, body: Rid, id: u64) {
+        self.record("body_attach_object_instance_id", || {
+            vec![body.to_variant(), (id as i64).to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             body.get_mut_base().set_instance_id(id);
         }
@@ -751,6 +952,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_attach_canvas_instance_id(&mut self, body: Rid, id: u64) {
+        self.record("body_attach_canvas_instance_id", || {
+            vec![body.to_variant(), (id as i64).to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             body.get_mut_base().set_canvas_instance_id(id);
         }
@@ -769,6 +973,9 @@ impl RapierPhysicsServerImpl {
         body: Rid,
         mode: CcdMode,
     ) {
+        self.record("body_set_continuous_collision_detection_mode", || {
+            vec![body.to_variant(), mode.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.set_continuous_collision_detection_mode(
@@ -793,6 +1000,9 @@ impl RapierPhysicsServerImpl {
 
     #[cfg(feature = "dim3")]
     pub fn body_set_enable_continuous_collision_detection(&mut self, body: Rid, enable: bool) {
+        self.record("body_set_enable_continuous_collision_detection", || {
+            vec![body.to_variant(), enable.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.set_continuous_collision_detection_mode(
@@ -816,6 +1026,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_set_collision_layer(&mut self, body: Rid, layer: u32) {
+        self.record("body_set_collision_layer", || {
+            vec![body.to_variant(), layer.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             body.get_mut_base()
                 .set_collision_layer(layer, &mut self.physics_data.physics_engine);
@@ -830,6 +1043,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_set_collision_mask(&mut self, body: Rid, mask: u32) {
+        self.record("body_set_collision_mask", || {
+            vec![body.to_variant(), mask.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             body.get_mut_base()
                 .set_collision_mask(mask, &mut self.physics_data.physics_engine);
@@ -843,13 +1059,29 @@ impl RapierPhysicsServerImpl {
         0
     }
 
-    pub(super) fn body_set_collision_priority(&mut self, _body: Rid, _priority: f32) {}
+    pub(super) fn body_set_collision_priority(&mut self, body: Rid, priority: f32) {
+        self.record("body_set_collision_priority", || {
+            vec![body.to_variant(), priority.to_variant()]
+        });
+        if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
+            body.get_mut_base().set_collision_priority(priority as real);
+            if let Some(body) = body.get_mut_body() {
+                body.apply_dominance_group(&mut self.physics_data.physics_engine);
+            }
+        }
+    }
 
-    pub(super) fn body_get_collision_priority(&self, _body: Rid) -> f32 {
+    pub(super) fn body_get_collision_priority(&self, body: Rid) -> f32 {
+        if let Some(body) = self.physics_data.collision_objects.get(&body) {
+            return body.get_base().get_collision_priority() as f32;
+        }
         0.0
     }
 
     pub(super) fn body_set_param(&mut self, body: Rid, param: BodyParameter, value: Variant) {
+        self.record("body_set_param", || {
+            vec![body.to_variant(), param.to_variant(), value.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.set_param(
@@ -872,6 +1104,7 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_reset_mass_properties(&mut self, body: Rid) {
+        self.record("body_reset_mass_properties", || vec![body.to_variant()]);
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.reset_mass_properties(
@@ -883,6 +1116,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_set_state(&mut self, body: Rid, state: BodyState, value: Variant) {
+        self.record("body_set_state", || {
+            vec![body.to_variant(), state.to_variant(), value.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.set_state(
@@ -906,6 +1142,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_apply_central_impulse(&mut self, body: Rid, impulse: Vector) {
+        self.record("body_apply_central_impulse", || {
+            vec![body.to_variant(), impulse.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.force_mass_update(
@@ -919,6 +1158,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_apply_torque_impulse(&mut self, body: Rid, impulse: Angle) {
+        self.record("body_apply_torque_impulse", || {
+            vec![body.to_variant(), impulse.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.force_mass_update(
@@ -932,6 +1174,13 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_apply_impulse(&mut self, body: Rid, impulse: Vector, position: Vector) {
+        self.record("body_apply_impulse", || {
+            vec![
+                body.to_variant(),
+                impulse.to_variant(),
+                position.to_variant(),
+            ]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.force_mass_update(
@@ -945,6 +1194,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_apply_central_force(&mut self, body: Rid, force: Vector) {
+        self.record("body_apply_central_force", || {
+            vec![body.to_variant(), force.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.force_mass_update(
@@ -958,6 +1210,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_apply_force(&mut self, body: Rid, force: Vector, position: Vector) {
+        self.record("body_apply_force", || {
+            vec![body.to_variant(), force.to_variant(), position.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.force_mass_update(
@@ -971,6 +1226,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_apply_torque(&mut self, body: Rid, torque: Angle) {
+        self.record("body_apply_torque", || {
+            vec![body.to_variant(), torque.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.force_mass_update(
@@ -984,6 +1242,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_add_constant_central_force(&mut self, body: Rid, force: Vector) {
+        self.record("body_add_constant_central_force", || {
+            vec![body.to_variant(), force.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.force_mass_update(
@@ -997,6 +1258,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_add_constant_force(&mut self, body: Rid, force: Vector, position: Vector) {
+        self.record("body_add_constant_force", || {
+            vec![body.to_variant(), force.to_variant(), position.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.force_mass_update(
@@ -1010,6 +1274,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_add_constant_torque(&mut self, body: Rid, torque: Angle) {
+        self.record("body_add_constant_torque", || {
+            vec![body.to_variant(), torque.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.force_mass_update(
@@ -1023,6 +1290,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_set_constant_force(&mut self, body: Rid, force: Vector) {
+        self.record("body_set_constant_force", || {
+            vec![body.to_variant(), force.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.force_mass_update(
@@ -1045,6 +1315,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_set_constant_torque(&mut self, body: Rid, torque: Angle) {
+        self.record("body_set_constant_torque", || {
+            vec![body.to_variant(), torque.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.force_mass_update(
@@ -1067,6 +1340,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_set_axis_velocity(&mut self, body: Rid, axis_velocity: Vector) {
+        self.record("body_set_axis_velocity", || {
+            vec![body.to_variant(), axis_velocity.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 let axis_velocity = axis_velocity;
@@ -1079,7 +1355,32 @@ impl RapierPhysicsServerImpl {
         }
     }
 
+    #[cfg(feature = "dim3")]
+    pub(super) fn body_set_axis_lock(&mut self, body: Rid, axis: BodyAxis, lock: bool) {
+        self.record("body_set_axis_lock", || {
+            vec![body.to_variant(), axis.to_variant(), lock.to_variant()]
+        });
+        if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
+            if let Some(body) = body.get_mut_body() {
+                body.set_axis_lock(axis, lock, &mut self.physics_data.physics_engine);
+            }
+        }
+    }
+
+    #[cfg(feature = "dim3")]
+    pub(super) fn body_is_axis_locked(&self, body: Rid, axis: BodyAxis) -> bool {
+        if let Some(body) = self.physics_data.collision_objects.get(&body) {
+            if let Some(body) = body.get_body() {
+                return body.is_axis_locked(axis);
+            }
+        }
+        false
+    }
+
     pub(super) fn body_add_collision_exception(&mut self, body: Rid, excepted_body: Rid) {
+        self.record("body_add_collision_exception", || {
+            vec![body.to_variant(), excepted_body.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.add_exception(excepted_body);
@@ -1089,6 +1390,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_remove_collision_exception(&mut self, body: Rid, excepted_body: Rid) {
+        self.record("body_remove_collision_exception", || {
+            vec![body.to_variant(), excepted_body.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.remove_exception(excepted_body);
@@ -1112,6 +1416,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_set_max_contacts_reported(&mut self, body: Rid, amount: i32) {
+        self.record("body_set_max_contacts_reported", || {
+            vec![body.to_variant(), amount.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.set_max_contacts_reported(amount);
@@ -1133,6 +1440,9 @@ impl RapierPhysicsServerImpl {
         _body: Rid,
         _threshold: f32,
     ) {
+        self.record("body_set_contacts_reported_depth_threshold", || {
+            vec![_body.to_variant(), _threshold.to_variant()]
+        });
     }
 
     pub(super) fn body_get_contacts_reported_depth_threshold(&self, _body: Rid) -> f32 {
@@ -1140,6 +1450,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_set_omit_force_integration(&mut self, body: Rid, enable: bool) {
+        self.record("body_set_omit_force_integration", || {
+            vec![body.to_variant(), enable.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             if let Some(body) = body.get_mut_body() {
                 body.set_omit_force_integration(enable);
@@ -1220,6 +1533,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn body_set_pickable(&mut self, body: Rid, pickable: bool) {
+        self.record("body_set_pickable", || {
+            vec![body.to_variant(), pickable.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             body.get_mut_base().set_pickable(pickable);
         }
@@ -1227,6 +1543,9 @@ impl RapierPhysicsServerImpl {
 
     #[cfg(feature = "dim3")]
     pub(super) fn body_set_ray_pickable(&mut self, body: Rid, pickable: bool) {
+        self.record("body_set_ray_pickable", || {
+            vec![body.to_variant(), pickable.to_variant()]
+        });
         if let Some(body) = self.physics_data.collision_objects.get_mut(&body) {
             body.get_mut_base().set_pickable(pickable);
         }
@@ -1282,10 +1601,12 @@ impl RapierPhysicsServerImpl {
         self.physics_data
             .joints
             .insert(rid, Box::new(RapierEmptyJoint::new()));
+        self.record_create("joint_create", rid);
         rid
     }
 
     pub(super) fn joint_clear(&mut self, rid: Rid) {
+        self.record("joint_clear", || vec![rid.to_variant()]);
         if let Some(mut prev_joint) = self.physics_data.joints.remove(&rid) {
             let mut joint = RapierEmptyJoint::new();
             joint
@@ -1300,6 +1621,9 @@ impl RapierPhysicsServerImpl {
 
     #[cfg(feature = "dim2")]
     pub(super) fn joint_set_param(&mut self, joint: Rid, param: JointParam, value: f32) {
+        self.record("joint_set_param", || {
+            vec![joint.to_variant(), param.to_variant(), value.to_variant()]
+        });
         if let Some(joint) = self.physics_data.joints.get_mut(&joint) {
             if param == JointParam::MAX_FORCE {
                 joint.get_mut_base().set_max_force(value);
@@ -1318,6 +1642,9 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn joint_disable_collisions_between_bodies(&mut self, joint: Rid, disable: bool) {
+        self.record("joint_disable_collisions_between_bodies", || {
+            vec![joint.to_variant(), disable.to_variant()]
+        });
         if let Some(joint) = self.physics_data.joints.get_mut(&joint) {
             joint
                 .get_mut_base()
@@ -1334,6 +1661,14 @@ impl RapierPhysicsServerImpl {
 
     #[cfg(feature = "dim2")]
     pub(super) fn joint_make_pin(&mut self, rid: Rid, anchor: Vector, body_a: Rid, body_b: Rid) {
+        self.record("joint_make_pin", || {
+            vec![
+                rid.to_variant(),
+                anchor.to_variant(),
+                body_a.to_variant(),
+                body_b.to_variant(),
+            ]
+        });
         let mut joint: Box<dyn IRapierJoint>;
         if let Some(body_a) = self.physics_data.collision_objects.get(&body_a)
             && let Some(body_b) = self.physics_data.collision_objects.get(&body_b)
@@ -1369,6 +1704,16 @@ impl RapierPhysicsServerImpl {
         body_a: Rid,
         body_b: Rid,
     ) {
+        self.record("joint_make_groove", || {
+            vec![
+                rid.to_variant(),
+                a_groove1.to_variant(),
+                a_groove2.to_variant(),
+                b_anchor.to_variant(),
+                body_a.to_variant(),
+                body_b.to_variant(),
+            ]
+        });
         let mut joint: Box<dyn IRapierJoint>;
         if let Some(body_a) = self.physics_data.collision_objects.get(&body_a)
             && let Some(body_b) = self.physics_data.collision_objects.get(&body_b)
@@ -1405,6 +1750,15 @@ impl RapierPhysicsServerImpl {
         body_a: Rid,
         body_b: Rid,
     ) {
+        self.record("joint_make_damped_spring", || {
+            vec![
+                rid.to_variant(),
+                anchor_a.to_variant(),
+                anchor_b.to_variant(),
+                body_a.to_variant(),
+                body_b.to_variant(),
+            ]
+        });
         let mut joint: Box<dyn IRapierJoint>;
         if let Some(body_a) = self.physics_data.collision_objects.get(&body_a)
             && let Some(body_b) = self.physics_data.collision_objects.get(&body_b)
@@ -1433,6 +1787,9 @@ impl RapierPhysicsServerImpl {
 
     #[cfg(feature = "dim2")]
     pub(super) fn pin_joint_set_flag(&mut self, joint: Rid, flag: PinJointFlag, enabled: bool) {
+        self.record("pin_joint_set_flag", || {
+            vec![joint.to_variant(), flag.to_variant(), enabled.to_variant()]
+        });
         if let Some(joint) = self.physics_data.joints.get_mut(&joint) {
             if let Some(joint) = joint.get_mut_pin() {
                 joint.set_flag(flag, enabled, &mut self.physics_data.physics_engine);
@@ -1452,6 +1809,9 @@ impl RapierPhysicsServerImpl {
 
     #[cfg(feature = "dim2")]
     pub(super) fn pin_joint_set_param(&mut self, joint: Rid, param: PinJointParam, value: f32) {
+        self.record("pin_joint_set_param", || {
+            vec![joint.to_variant(), param.to_variant(), value.to_variant()]
+        });
         if let Some(joint) = self.physics_data.joints.get_mut(&joint) {
             if let Some(joint) = joint.get_mut_pin() {
                 joint.set_param(param, value, &mut self.physics_data.physics_engine);
@@ -1476,6 +1836,9 @@ impl RapierPhysicsServerImpl {
         param: DampedSpringParam,
         value: f32,
     ) {
+        self.record("damped_spring_joint_set_param", || {
+            vec![joint.to_variant(), param.to_variant(), value.to_variant()]
+        });
         if let Some(joint) = self.physics_data.joints.get_mut(&joint) {
             if let Some(joint) = joint.get_mut_damped_spring() {
                 joint.set_param(param, value, &mut self.physics_data.physics_engine);
@@ -1511,6 +1874,7 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn free_rid(&mut self, rid: Rid) {
+        self.record("free_rid", || vec![rid.to_variant()]);
         if let Some(mut shape) = self.physics_data.shapes.remove(&rid) {
             for (owner, _) in shape.get_base().get_owners() {
                 if let Some(body) = self.physics_data.collision_objects.get_mut(owner) {
@@ -1573,6 +1937,7 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn set_active(&mut self, active: bool) {
+        self.record("set_active", || vec![active.to_variant()]);
         self.active = active;
     }
 
@@ -1586,6 +1951,10 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn step(&mut self, step: f32) {
+        self.record("step", || vec![step.to_variant()]);
+        if let Some(recorder) = &mut self.recorder {
+            recorder.flush();
+        }
         if !self.active {
             return;
         }
@@ -1614,10 +1983,12 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn sync(&mut self) {
+        self.record("sync", Vec::new);
         self.doing_sync = true;
     }
 
     pub(super) fn flush_queries(&mut self) -> Vec<Callable> {
+        self.record("flush_queries", Vec::new);
         if !self.active {
             return Vec::default();
         }
@@ -1633,6 +2004,7 @@ impl RapierPhysicsServerImpl {
     }
 
     pub(super) fn end_sync(&mut self) {
+        self.record("end_sync", Vec::new);
         self.doing_sync = false;
     }
 
@@ -0,0 +1,167 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Write;
+
+use godot::classes::ProjectSettings;
+use godot::engine::utilities::bytes_to_var;
+use godot::engine::utilities::var_to_bytes;
+use godot::prelude::*;
+use hashbrown::HashMap;
+
+use crate::servers::RapierPhysicsServer;
+use crate::types::*;
+// Rids are stored by id, as they are different each time the calls are replayed
+const RID_KEY: &str = "__recorded_rid";
+fn rid_to_record(rid: Rid) -> Variant {
+    dict! { RID_KEY: rid.to_u64() as i64 }.to_variant()
+}
+fn record_to_rid_id(value: &Variant) -> Option<u64> {
+    let value = value.try_to::<Dictionary>().ok()?;
+    if value.len() != 1 {
+        return None;
+    }
+    let id = value.get(RID_KEY)?.try_to::<i64>().ok()?;
+    Some(id as u64)
+}
+// Each call is stored as the length of the encoded call, followed by [method, args, result]
+pub struct ServerRecorder {
+    writer: BufWriter<File>,
+}
+impl ServerRecorder {
+    pub fn new(path: GString) -> Option<Self> {
+        let path = ProjectSettings::singleton()
+            .globalize_path(path)
+            .to_string();
+        match File::create(&path) {
+            Ok(file) => Some(Self {
+                writer: BufWriter::new(file),
+            }),
+            Err(e) => {
+                godot_error!("Failed to create recording {}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    pub fn record(&mut self, method: &str, args: Vec<Variant>, result: Rid) {
+        let mut recorded_args = VariantArray::new();
+        for arg in args {
+            if let Ok(rid) = arg.try_to::<Rid>() {
+                recorded_args.push(rid_to_record(rid));
+            } else {
+                recorded_args.push(arg);
+            }
+        }
+        let call = varray![method, recorded_args, rid_to_record(result)];
+        let bytes = var_to_bytes(call.to_variant());
+        let written = self
+            .writer
+            .write_all(&(bytes.len() as u32).to_le_bytes())
+            .and_then(|_| self.writer.write_all(bytes.as_slice()));
+        if let Err(e) = written {
+            godot_error!("Failed to record {}: {}", method, e);
+        }
+    }
+
+    // Called once per step, so that a crash loses at most one step
+    pub fn flush(&mut self) {
+        if let Err(e) = self.writer.flush() {
+            godot_error!("Failed to write recording: {}", e);
+        }
+    }
+}
+impl Drop for ServerRecorder {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+fn read_calls(path: GString) -> Option<Vec<VariantArray>> {
+    let path = ProjectSettings::singleton()
+        .globalize_path(path)
+        .to_string();
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(e) => {
+            godot_error!("Failed to open recording {}: {}", path, e);
+            return None;
+        }
+    };
+    let mut reader = BufReader::new(file);
+    let mut calls = Vec::new();
+    let mut len_bytes = [0u8; 4];
+    while reader.read_exact(&mut len_bytes).is_ok() {
+        let mut bytes = vec![0u8; u32::from_le_bytes(len_bytes) as usize];
+        if let Err(e) = reader.read_exact(&mut bytes) {
+            godot_error!("Recording {} is truncated: {}", path, e);
+            break;
+        }
+        match bytes_to_var(PackedByteArray::from(bytes.as_slice())).try_to::<VariantArray>() {
+            Ok(call) if call.len() == 3 => calls.push(call),
+            _ => {
+                godot_error!("Recording {} has an invalid call", path);
+                return None;
+            }
+        }
+    }
+    Some(calls)
+}
+// Replays the calls against the current server. Should be done on a fresh server, eg. from an
+// empty scene, so that only the recorded objects exist.
+pub fn replay_calls(path: GString) -> bool {
+    let Some(calls) = read_calls(path) else {
+        return false;
+    };
+    let mut physics_server = PhysicsServer::singleton();
+    let Ok(mut rapier_server) = physics_server.clone().try_cast::<RapierPhysicsServer>() else {
+        godot_error!("Recordings can only be replayed with the Rapier physics server");
+        return false;
+    };
+    let mut rids: HashMap<u64, Rid> = HashMap::default();
+    for call in calls {
+        let method = call.at(0).to::<GString>().to_string();
+        let recorded_args = call.at(1).to::<VariantArray>();
+        let mut args = VariantArray::new();
+        for arg in recorded_args.iter_shared() {
+            if let Some(id) = record_to_rid_id(&arg) {
+                let rid = rids.get(&id).copied().unwrap_or(Rid::Invalid);
+                args.push(rid.to_variant());
+            } else {
+                args.push(arg);
+            }
+        }
+        // These are only called by the engine, so they aren't part of the server api
+        match method.as_str() {
+            "step" => {
+                let step = args.at(0).to::<f32>();
+                rapier_server.bind_mut().implementation.step(step);
+            }
+            "sync" => rapier_server.bind_mut().implementation.sync(),
+            "flush_queries" => {
+                let mut server = rapier_server.bind_mut();
+                let _ = server.implementation.flush_queries();
+                server.implementation.finish_flushing_queries();
+            }
+            "end_sync" => rapier_server.bind_mut().implementation.end_sync(),
+            "space_set_debug_contacts" => {
+                let space = args.at(0).to::<Rid>();
+                let max_contacts = args.at(1).to::<i32>();
+                rapier_server
+                    .bind_mut()
+                    .implementation
+                    .space_set_debug_contacts(space, max_contacts);
+            }
+            _ => {
+                let result = physics_server.callv(StringName::from(method.as_str()), args);
+                if let Some(recorded_id) = record_to_rid_id(&call.at(2))
+                    && recorded_id != 0
+                    && let Ok(rid) = result.try_to::<Rid>()
+                {
+                    rids.insert(recorded_id, rid);
+                }
+            }
+        }
+    }
+    true
+}
@@ -0,0 +1,158 @@
+use godot::prelude::*;
+use hashbrown::HashMap;
+use hashbrown::HashSet;
+use rapier::dynamics::ImpulseJointHandle;
+
+use crate::bodies::rapier_area::RapierArea;
+use crate::bodies::rapier_body::RapierBody;
+use crate::bodies::rapier_collision_object::IRapierCollisionObject;
+use crate::joints::rapier_joint::IRapierJoint;
+use crate::rapier_wrapper::prelude::*;
+use crate::servers::rapier_physics_server_export::*;
+use crate::servers::rapier_physics_server_extra::PhysicsData;
+use crate::spaces::rapier_space::RapierSpace;
+// Only the objects of the worlds are saved, the pipelines are rebuilt on load
+#[derive(serde::Serialize)]
+pub struct PhysicsDataSaveState<'a> {
+    pub spaces: Vec<(Rid, &'a RapierSpace, &'a PhysicsObjects)>,
+    pub bodies: Vec<&'a RapierBody>,
+    pub areas: Vec<&'a RapierArea>,
+    pub joints: Vec<(Rid, &'a dyn IRapierJoint)>,
+}
+#[derive(serde::Deserialize)]
+pub struct PhysicsDataLoadState {
+    pub spaces: Vec<(Rid, RapierSpace, PhysicsObjects)>,
+    pub bodies: Vec<RapierBody>,
+    pub areas: Vec<RapierArea>,
+    pub joints: Vec<(Rid, Box<dyn IRapierJoint>)>,
+}
+impl PhysicsData {
+    pub fn save_state(&self) -> PackedByteArray {
+        let mut state = PhysicsDataSaveState {
+            spaces: Vec::with_capacity(self.spaces.len()),
+            bodies: Vec::new(),
+            areas: Vec::new(),
+            joints: Vec::with_capacity(self.joints.len()),
+        };
+        for (rid, space) in self.spaces.iter() {
+            if let Some(physics_objects) = self.physics_engine.world_export(space.get_handle()) {
+                state.spaces.push((*rid, space, physics_objects));
+            }
+        }
+        for collision_object in self.collision_objects.values() {
+            if let Some(body) = collision_object.get_body() {
+                state.bodies.push(body);
+            } else if let Some(area) = collision_object.get_area() {
+                state.areas.push(area);
+            }
+        }
+        for (rid, joint) in self.joints.iter() {
+            state.joints.push((*rid, joint.as_ref()));
+        }
+        export_binary(ExportKind::State, &state)
+    }
+
+    // Only objects that still exist are loaded, so that all RIDs stay valid. The worlds are
+    // imported again, which drops the objects freed since the save, and objects created since are
+    // added back. Contacts are found again on the next step.
+    pub fn load_state(&mut self, data: PackedByteArray) {
+        let state = match import_binary::<PhysicsDataLoadState>(ExportKind::State, data.as_slice())
+        {
+            Ok(state) => state,
+            Err(e) => {
+                godot_error!("Failed to deserialize state: {}", e);
+                return;
+            }
+        };
+        let mut loaded_worlds = Vec::new();
+        for (rid, mut loaded_space, physics_objects) in state.spaces {
+            loaded_space.detach_world();
+            if let Some(space) = self.spaces.get_mut(&rid) {
+                space.set_imported_state(&mut loaded_space);
+                loaded_worlds.push((rid, space.get_handle(), physics_objects));
+            }
+        }
+        let mut loaded_collision_objects = HashSet::default();
+        for loaded_body in state.bodies {
+            let rid = loaded_body.get_base().get_rid();
+            if let Some(body) = self.collision_objects.get_mut(&rid)
+                && let Some(body) = body.get_mut_body()
+            {
+                body.load_state(loaded_body, &mut self.physics_engine);
+                loaded_collision_objects.insert(rid);
+            }
+        }
+        for loaded_area in state.areas {
+            let rid = loaded_area.get_base().get_rid();
+            if let Some(area) = self.collision_objects.get_mut(&rid)
+                && let Some(area) = area.get_mut_area()
+            {
+                area.load_state(loaded_area);
+                loaded_collision_objects.insert(rid);
+            }
+        }
+        let mut loaded_spaces = HashSet::default();
+        let mut joint_handles = HashMap::default();
+        for (rid, space_handle, physics_objects) in loaded_worlds {
+            let (space_joint_handles, _) =
+                RapierSpace::import_world(space_handle, self, physics_objects);
+            joint_handles.insert(rid, space_joint_handles);
+            loaded_spaces.insert(rid);
+        }
+        // Objects created since the save have no body in the loaded world, add them again
+        for (rid, collision_object) in self.collision_objects.iter_mut() {
+            let space = collision_object.get_base().get_space();
+            if loaded_collision_objects.contains(rid) || !loaded_spaces.contains(&space) {
+                continue;
+            }
+            collision_object.get_mut_base().reset_handles();
+            collision_object.set_space(
+                Rid::Invalid,
+                &mut self.physics_engine,
+                &mut self.spaces,
+                &mut self.shapes,
+            );
+            collision_object.set_space(
+                space,
+                &mut self.physics_engine,
+                &mut self.spaces,
+                &mut self.shapes,
+            );
+        }
+        let mut loaded_joints = HashSet::default();
+        for (rid, mut loaded_joint) in state.joints {
+            let space = loaded_joint.get_base().get_space();
+            let joint_handle = joint_handles
+                .get(&space)
+                .and_then(|space_joint_handles| {
+                    space_joint_handles.get(&loaded_joint.get_base().get_handle())
+                })
+                .copied()
+                .unwrap_or(ImpulseJointHandle::invalid());
+            loaded_joint.get_mut_base().set_handle(joint_handle);
+            if let Some(joint) = self.joints.get_mut(&rid) {
+                std::mem::swap(joint, &mut loaded_joint);
+                loaded_joints.insert(rid);
+            } else if let Some(space) = self.spaces.get(&space) {
+                // The joint no longer exists
+                self.physics_engine
+                    .destroy_joint(space.get_handle(), joint_handle);
+            }
+        }
+        for (rid, joint) in self.joints.iter_mut() {
+            if loaded_joints.contains(rid) || !loaded_spaces.contains(&joint.get_base().get_space())
+            {
+                continue;
+            }
+            if joint.get_base().is_valid() {
+                godot_error!(
+                    "Joint {} was created after the state was saved and is disabled, create it again",
+                    rid
+                );
+            }
+            joint
+                .get_mut_base()
+                .set_handle(ImpulseJointHandle::invalid());
+        }
+    }
+}
@@ -9,6 +9,7 @@ const SOLVER_NUM_INTERNAL_PGS_ITERATIONS: &str =
     "physics/rapier/solver/num_internal_pgs_iterations";
 const SOLVER_MAX_CCD_SUBSTEPS: &str = "physics/rapier/solver/max_ccd_substeps";
 const CONTACT_SKIN: &str = "physics/rapier/solver/polygon_contact_skin";
+const SHAPE_SUBDIVISIONS: &str = "physics/rapier/shapes/scaled_shape_subdivisions";
 const FLUID_PARTICLE_RADIUS: &str = "physics/rapier/fluid/fluid_particle_radius";
 const FLUID_SMOOTHING_FACTOR: &str = "physics/rapier/fluid/fluid_smoothing_factor";
 #[cfg(feature = "dim2")]
@@ -92,6 +93,12 @@ impl RapierProjectSettings {
             "0,10,0.00001,or_greater",
             true,
         );
+        register_setting_ranged(
+            SHAPE_SUBDIVISIONS,
+            Variant::from(20),
+            "3,64,or_greater",
+            true,
+        );
         register_setting_ranged(
             FLUID_PARTICLE_RADIUS,
             Variant::from(20.0),
@@ -152,6 +159,10 @@ impl RapierProjectSettings {
         RapierProjectSettings::get_setting_double(CONTACT_SKIN) as Real
     }
 
+    pub fn get_shape_subdivisions() -> i64 {
+        RapierProjectSettings::get_setting_int(SHAPE_SUBDIVISIONS)
+    }
+
     pub fn get_length_unit() -> Real {
         RapierProjectSettings::get_setting_double(LENGTH_UNIT) as Real
     }
@@ -63,7 +63,17 @@ impl IRapierShape for RapierConcavePolygonShape2D {
     }
 
     fn create_rapier_shape(&mut self, physics_engine: &mut PhysicsEngine) -> ShapeHandle {
-        if self.points.len() >= 3 {
+        // Merging builds the polyline from the segments, connecting the ones that share a point
+        if self.base.get_mesh_flags() & MESH_FLAG_MERGE_DUPLICATE_VERTICES != 0
+            && !self.segments.is_empty()
+        {
+            let mut rapier_points = Vec::with_capacity(self.segments.len() * 2);
+            for segment in self.segments.iter() {
+                rapier_points.push(vector_to_rapier(self.points[segment[0] as usize]));
+                rapier_points.push(vector_to_rapier(self.points[segment[1] as usize]));
+            }
+            physics_engine.shape_create_concave_segments(&rapier_points)
+        } else if self.points.len() >= 3 {
             let point_count = self.points.len();
             let mut rapier_points = Vec::with_capacity(point_count + 1);
             for i in 0..point_count {
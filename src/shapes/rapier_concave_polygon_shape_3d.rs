@@ -0,0 +1,135 @@
+use godot::classes::physics_server_3d::*;
+use godot::prelude::*;
+
+use crate::rapier_wrapper::prelude::*;
+use crate::shapes::rapier_shape::*;
+use crate::types::*;
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct RapierConcavePolygonShape3D {
+    faces: Vec<Vector3>,
+    backface_collision: bool,
+    base: RapierShapeBase,
+}
+impl RapierConcavePolygonShape3D {
+    pub fn new(rid: Rid) -> Self {
+        Self {
+            faces: Vec::new(),
+            backface_collision: false,
+            base: RapierShapeBase::new(rid),
+        }
+    }
+}
+#[cfg_attr(feature = "serde-serialize", typetag::serde)]
+impl IRapierShape for RapierConcavePolygonShape3D {
+    fn get_base(&self) -> &RapierShapeBase {
+        &self.base
+    }
+
+    fn get_mut_base(&mut self) -> &mut RapierShapeBase {
+        &mut self.base
+    }
+
+    fn get_type(&self) -> ShapeType {
+        ShapeType::CONCAVE_POLYGON
+    }
+
+    fn get_moment_of_inertia(&self, mass: f32, scale: Vector) -> Vector3 {
+        // use bad AABB approximation
+        let extents = self.compute_aabb().size * scale * 0.5;
+        Vector3::new(
+            (mass / 3.0) * (extents.y * extents.y + extents.z * extents.z),
+            (mass / 3.0) * (extents.x * extents.x + extents.z * extents.z),
+            (mass / 3.0) * (extents.x * extents.x + extents.y * extents.y),
+        )
+    }
+
+    fn allows_one_way_collision(&self) -> bool {
+        true
+    }
+
+    fn create_rapier_shape(&mut self, physics_engine: &mut PhysicsEngine) -> ShapeHandle {
+        if self.faces.len() >= 3 {
+            let mut rapier_faces = Vec::with_capacity(self.faces.len());
+            for face in self.faces.iter() {
+                rapier_faces.push(vector_to_rapier(*face));
+            }
+            physics_engine.shape_create_concave_trimesh(
+                &rapier_faces,
+                self.backface_collision,
+                self.base.get_mesh_flags(),
+            )
+        } else {
+            godot_error!("ConcavePolygon3D must have at least one face");
+            ShapeHandle::default()
+        }
+    }
+
+    fn set_data(&mut self, data: Variant, physics_engine: &mut PhysicsEngine) {
+        match data.get_type() {
+            VariantType::DICTIONARY => {
+                let dictionary: Dictionary = data.to();
+                let Some(faces) = dictionary.get("faces") else {
+                    godot_error!("ConcavePolygon3D data must contain faces");
+                    return;
+                };
+                let Ok(faces) = faces.try_to::<PackedVector3Array>() else {
+                    godot_error!("ConcavePolygon3D faces must be a PackedVector3Array");
+                    return;
+                };
+                let len = faces.len();
+                // No faces clears the shape
+                if len == 0 {
+                    self.faces.clear();
+                    self.base
+                        .set_handle(ShapeHandle::default(), Aabb::default(), physics_engine);
+                    return;
+                }
+                if len % 3 != 0 {
+                    godot_error!("ConcavePolygon3D must have a multiple of three points");
+                    return;
+                }
+                self.faces = faces.to_vec();
+                self.backface_collision = false;
+                if let Some(backface_collision) = dictionary.get("backface_collision") {
+                    if let Ok(backface_collision) = backface_collision.try_to::<bool>() {
+                        self.backface_collision = backface_collision;
+                    }
+                }
+            }
+            _ => {
+                godot_error!("Invalid shape data");
+                return;
+            }
+        }
+        let handle = self.create_rapier_shape(physics_engine);
+        self.base
+            .set_handle(handle, self.compute_aabb(), physics_engine);
+    }
+
+    fn get_data(&self) -> Variant {
+        let dictionary = dict! {
+            "faces": PackedVector3Array::from(self.faces.as_slice()),
+            "backface_collision": self.backface_collision,
+        };
+        dictionary.to_variant()
+    }
+
+    fn get_handle(&self) -> ShapeHandle {
+        self.base.get_handle()
+    }
+}
+impl RapierConcavePolygonShape3D {
+    fn compute_aabb(&self) -> Aabb {
+        let Some(first) = self.faces.first() else {
+            return Aabb::default();
+        };
+        let mut aabb = Aabb::new(*first, Vector3::ZERO);
+        for face in self.faces.iter() {
+            aabb = aabb.expand(*face);
+        }
+        aabb
+    }
+}
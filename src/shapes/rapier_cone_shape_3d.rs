@@ -0,0 +1,112 @@
+use godot::classes::physics_server_3d::*;
+use godot::prelude::*;
+
+use crate::rapier_wrapper::prelude::*;
+use crate::shapes::rapier_shape::*;
+use crate::types::*;
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct RapierConeShape3D {
+    height: real,
+    radius: real,
+    base: RapierShapeBase,
+}
+impl RapierConeShape3D {
+    pub fn new(rid: Rid) -> Self {
+        Self {
+            height: 0.0,
+            radius: 0.0,
+            base: RapierShapeBase::new(rid),
+        }
+    }
+}
+#[cfg_attr(feature = "serde-serialize", typetag::serde)]
+impl IRapierShape for RapierConeShape3D {
+    fn get_base(&self) -> &RapierShapeBase {
+        &self.base
+    }
+
+    fn get_mut_base(&mut self) -> &mut RapierShapeBase {
+        &mut self.base
+    }
+
+    fn get_type(&self) -> ShapeType {
+        ShapeType::CUSTOM
+    }
+
+    fn get_moment_of_inertia(&self, p_mass: f32, _scale: Vector) -> Vector3 {
+        // use bad AABB approximation
+        let extents = self.compute_aabb().size * 0.5;
+        Vector3::new(
+            (p_mass / 3.0) * (extents.y * extents.y + extents.z * extents.z),
+            (p_mass / 3.0) * (extents.x * extents.x + extents.z * extents.z),
+            (p_mass / 3.0) * (extents.x * extents.x + extents.y * extents.y),
+        )
+    }
+
+    fn allows_one_way_collision(&self) -> bool {
+        true
+    }
+
+    fn create_rapier_shape(&mut self, physics_engine: &mut PhysicsEngine) -> ShapeHandle {
+        let border_radius = self.base.get_border_radius();
+        if border_radius > 0.0 {
+            return physics_engine.shape_create_round_cone(
+                self.height / 2.0,
+                self.radius,
+                border_radius,
+            );
+        }
+        physics_engine.shape_create_cone(self.height / 2.0, self.radius)
+    }
+
+    fn set_data(&mut self, data: Variant, physics_engine: &mut PhysicsEngine) {
+        match data.get_type() {
+            VariantType::VECTOR2 => {
+                let vector_data: Vector2 = data.to();
+                self.height = vector_data.y;
+                self.radius = vector_data.x;
+            }
+            VariantType::DICTIONARY => {
+                let dictionary: Dictionary = data.to();
+                if let Some(height) = dictionary.get("height") {
+                    if let Ok(height) = height.try_to::<real>() {
+                        self.height = height;
+                    }
+                }
+                if let Some(radius) = dictionary.get("radius") {
+                    if let Ok(radius) = radius.try_to::<real>() {
+                        self.radius = radius;
+                    }
+                }
+            }
+            _ => {
+                godot_error!("Invalid shape data");
+                return;
+            }
+        }
+        let handle = self.create_rapier_shape(physics_engine);
+        self.base
+            .set_handle(handle, self.compute_aabb(), physics_engine);
+    }
+
+    fn get_data(&self) -> Variant {
+        let dictionary = dict! {
+            "height": self.height,
+            "radius": self.radius,
+        };
+        dictionary.to_variant()
+    }
+
+    fn get_handle(&self) -> ShapeHandle {
+        self.base.get_handle()
+    }
+}
+impl RapierConeShape3D {
+    fn compute_aabb(&self) -> Aabb {
+        let he = Vector3::new(self.radius, self.height * 0.5, self.radius);
+        Aabb::new(-he, he * 2.0).grow(self.base.get_border_radius())
+    }
+}
@@ -31,7 +31,7 @@ impl RapierConvexPolygonShape {
         for point in self.points.as_slice() {
             aabb_new = aabb_new.expand(*point * scale);
         }
-        aabb_new
+        aabb_new.grow(self.base.get_border_radius())
     }
 }
 #[cfg_attr(feature = "serde-serialize", typetag::serde)]
@@ -81,6 +81,11 @@ impl IRapierShape for RapierConvexPolygonShape {
             for point in self.points.as_slice() {
                 rapier_points.push(vector_to_rapier(*point));
             }
+            let border_radius = self.base.get_border_radius();
+            if border_radius > 0.0 {
+                return physics_engine
+                    .shape_create_round_convex_polyline(&rapier_points, border_radius);
+            }
             physics_engine.shape_create_convex_polyline(&rapier_points)
         } else {
             godot_error!("ConvexPolygon must have at least three point");
@@ -51,6 +51,14 @@ impl IRapierShape for RapierCylinderShape3D {
     }
 
     fn create_rapier_shape(&mut self, physics_engine: &mut PhysicsEngine) -> ShapeHandle {
+        let border_radius = self.base.get_border_radius();
+        if border_radius > 0.0 {
+            return physics_engine.shape_create_round_cylinder(
+                (self.height / 2.0) - self.radius,
+                self.radius,
+                border_radius,
+            );
+        }
         physics_engine.shape_create_cylinder((self.height / 2.0) - self.radius, self.radius)
     }
 
@@ -103,6 +111,6 @@ impl IRapierShape for RapierCylinderShape3D {
 impl RapierCylinderShape3D {
     fn compute_aabb(&self) -> Aabb {
         let he = Vector3::new(self.radius, self.height * 0.5, self.radius);
-        Aabb::new(-he, he * 2.0)
+        Aabb::new(-he, he * 2.0).grow(self.base.get_border_radius())
     }
 }
@@ -0,0 +1,164 @@
+use godot::classes::physics_server_3d::*;
+use godot::prelude::*;
+
+use crate::rapier_wrapper::prelude::*;
+use crate::shapes::rapier_shape::*;
+use crate::types::*;
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct RapierHeightMapShape3D {
+    heights: Vec<real>,
+    width: i32,
+    depth: i32,
+    base: RapierShapeBase,
+}
+impl RapierHeightMapShape3D {
+    pub fn new(rid: Rid) -> Self {
+        Self {
+            heights: Vec::new(),
+            width: 0,
+            depth: 0,
+            base: RapierShapeBase::new(rid),
+        }
+    }
+}
+#[cfg_attr(feature = "serde-serialize", typetag::serde)]
+impl IRapierShape for RapierHeightMapShape3D {
+    fn get_base(&self) -> &RapierShapeBase {
+        &self.base
+    }
+
+    fn get_mut_base(&mut self) -> &mut RapierShapeBase {
+        &mut self.base
+    }
+
+    fn get_type(&self) -> ShapeType {
+        ShapeType::HEIGHTMAP
+    }
+
+    fn get_moment_of_inertia(&self, p_mass: f32, scale: Vector) -> Vector3 {
+        // use bad AABB approximation
+        let extents = self.compute_aabb().size * scale * 0.5;
+        Vector3::new(
+            (p_mass / 3.0) * (extents.y * extents.y + extents.z * extents.z),
+            (p_mass / 3.0) * (extents.x * extents.x + extents.z * extents.z),
+            (p_mass / 3.0) * (extents.x * extents.x + extents.y * extents.y),
+        )
+    }
+
+    fn allows_one_way_collision(&self) -> bool {
+        false
+    }
+
+    fn create_rapier_shape(&mut self, physics_engine: &mut PhysicsEngine) -> ShapeHandle {
+        if self.width < 2 || self.depth < 2 {
+            return ShapeHandle::default();
+        }
+        physics_engine.shape_create_heightmap(
+            &self.heights,
+            self.width as usize,
+            self.depth as usize,
+            self.base.get_mesh_flags(),
+        )
+    }
+
+    fn set_data(&mut self, data: Variant, physics_engine: &mut PhysicsEngine) {
+        if data.get_type() != VariantType::DICTIONARY {
+            godot_error!("Invalid shape data");
+            return;
+        }
+        let dictionary: Dictionary = data.to();
+        let (Some(width), Some(depth), Some(heights)) = (
+            dictionary.get("width"),
+            dictionary.get("depth"),
+            dictionary.get("heights"),
+        ) else {
+            godot_error!("Invalid shape data");
+            return;
+        };
+        let (Ok(width), Ok(depth)) = (width.try_to::<i32>(), depth.try_to::<i32>()) else {
+            godot_error!("Invalid shape data");
+            return;
+        };
+        let heights: Vec<real> = match heights.get_type() {
+            VariantType::PACKED_FLOAT32_ARRAY => heights
+                .to::<PackedFloat32Array>()
+                .as_slice()
+                .iter()
+                .map(|height| *height as real)
+                .collect(),
+            VariantType::PACKED_FLOAT64_ARRAY => heights
+                .to::<PackedFloat64Array>()
+                .as_slice()
+                .iter()
+                .map(|height| *height as real)
+                .collect(),
+            _ => {
+                godot_error!("Invalid shape data");
+                return;
+            }
+        };
+        if width < 2 || depth < 2 {
+            godot_error!("Heightmap width and depth must be at least 2");
+            return;
+        }
+        if heights.len() != (width * depth) as usize {
+            godot_error!(
+                "Heightmap has {} heights, expected width * depth = {}",
+                heights.len(),
+                width * depth
+            );
+            return;
+        }
+        self.width = width;
+        self.depth = depth;
+        self.heights = heights;
+        let handle = self.create_rapier_shape(physics_engine);
+        self.base
+            .set_handle(handle, self.compute_aabb(), physics_engine);
+    }
+
+    fn get_data(&self) -> Variant {
+        let heights = PackedFloatArray::from(self.heights.as_slice());
+        let (min_height, max_height) = self.get_height_range();
+        let dictionary = dict! {
+            "width": self.width,
+            "depth": self.depth,
+            "heights": heights,
+            "min_height": min_height,
+            "max_height": max_height,
+        };
+        dictionary.to_variant()
+    }
+
+    fn get_handle(&self) -> ShapeHandle {
+        self.base.get_handle()
+    }
+}
+impl RapierHeightMapShape3D {
+    fn get_height_range(&self) -> (real, real) {
+        if self.heights.is_empty() {
+            return (0.0, 0.0);
+        }
+        let mut min_height = real::MAX;
+        let mut max_height = real::MIN;
+        for &height in &self.heights {
+            min_height = min_height.min(height);
+            max_height = max_height.max(height);
+        }
+        (min_height, max_height)
+    }
+
+    // Centered on x and z, like Godot does
+    fn compute_aabb(&self) -> Aabb {
+        let (min_height, max_height) = self.get_height_range();
+        let width = (self.width - 1).max(0) as real;
+        let depth = (self.depth - 1).max(0) as real;
+        Aabb::new(
+            Vector3::new(-width * 0.5, min_height, -depth * 0.5),
+            Vector3::new(width, max_height - min_height, depth),
+        )
+    }
+}
@@ -23,6 +23,11 @@ impl RapierRectangleShape {
             base: RapierShapeBase::new(rid),
         }
     }
+
+    fn compute_aabb(&self) -> Rect {
+        let he = self.half_extents + Vector::ONE * self.base.get_border_radius();
+        Rect::new(-he, he * 2.0)
+    }
 }
 #[cfg_attr(feature = "serde-serialize", typetag::serde)]
 impl IRapierShape for RapierRectangleShape {
@@ -46,15 +51,16 @@ impl IRapierShape for RapierRectangleShape {
 
     #[cfg(feature = "dim2")]
     fn get_moment_of_inertia(&self, mass: f32, scale: Vector) -> f32 {
-        let he2 = self.half_extents * 2.0 * scale;
+        let he2 = self.compute_aabb().size * scale;
         mass * he2.dot(he2) / 12.0
     }
 
     #[cfg(feature = "dim3")]
     fn get_moment_of_inertia(&self, mass: f32, scale: Vector) -> Vector3 {
-        let lx = self.half_extents.x;
-        let ly = self.half_extents.y;
-        let lz = self.half_extents.z;
+        let half_extents = self.compute_aabb().size * 0.5;
+        let lx = half_extents.x;
+        let ly = half_extents.y;
+        let lz = half_extents.z;
         Vector3::new(
             (mass / 3.0) * (ly * ly + lz * lz),
             (mass / 3.0) * (lx * lx + lz * lz),
@@ -68,15 +74,19 @@ impl IRapierShape for RapierRectangleShape {
 
     fn create_rapier_shape(&mut self, physics_engine: &mut PhysicsEngine) -> ShapeHandle {
         let v = vector_to_rapier(self.half_extents) * 2.0;
+        let border_radius = self.base.get_border_radius();
+        if border_radius > 0.0 {
+            return physics_engine.shape_create_round_box(v, border_radius);
+        }
         physics_engine.shape_create_box(v)
     }
 
     fn set_data(&mut self, data: Variant, physics_engine: &mut PhysicsEngine) {
         if let Ok(v) = data.try_to() {
             self.half_extents = v;
-            let aabb = Rect::new(-self.half_extents, self.half_extents * 2.0);
             let handle = self.create_rapier_shape(physics_engine);
-            self.base.set_handle(handle, aabb, physics_engine);
+            self.base
+                .set_handle(handle, self.compute_aabb(), physics_engine);
         } else {
             godot_error!("Invalid data type for RapierRectangleShape");
         }
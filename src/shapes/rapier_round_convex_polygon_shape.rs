@@ -0,0 +1,132 @@
+#[cfg(feature = "dim2")]
+use godot::classes::physics_server_2d::*;
+#[cfg(feature = "dim3")]
+use godot::classes::physics_server_3d::*;
+use godot::prelude::*;
+
+use crate::rapier_wrapper::prelude::*;
+use crate::shapes::rapier_shape::*;
+use crate::types::*;
+// The border radius is added around the convex hull of the points
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct RapierRoundConvexPolygonShape {
+    points: Vec<Vector>,
+    border_radius: real,
+    base: RapierShapeBase,
+}
+impl RapierRoundConvexPolygonShape {
+    pub fn new(rid: Rid) -> Self {
+        Self {
+            points: Vec::new(),
+            border_radius: 0.0,
+            base: RapierShapeBase::new(rid),
+        }
+    }
+
+    fn compute_aabb(&self, scale: Vector) -> Rect {
+        let mut aabb_new = Rect::new(Vector::ZERO, Vector::ZERO);
+        for point in self.points.iter() {
+            aabb_new = aabb_new.expand(*point * scale);
+        }
+        aabb_new.grow(self.border_radius)
+    }
+}
+#[cfg_attr(feature = "serde-serialize", typetag::serde)]
+impl IRapierShape for RapierRoundConvexPolygonShape {
+    fn get_base(&self) -> &RapierShapeBase {
+        &self.base
+    }
+
+    fn get_mut_base(&mut self) -> &mut RapierShapeBase {
+        &mut self.base
+    }
+
+    fn has_data_border_radius(&self) -> bool {
+        true
+    }
+
+    fn get_type(&self) -> ShapeType {
+        ShapeType::CUSTOM
+    }
+
+    #[cfg(feature = "dim2")]
+    fn get_moment_of_inertia(&self, mass: f32, scale: Vector) -> f32 {
+        if self.points.len() < 3 {
+            return 0.0;
+        }
+        let aabb_new = self.compute_aabb(scale);
+        mass * aabb_new.size.dot(aabb_new.size) / 12.0
+    }
+
+    #[cfg(feature = "dim3")]
+    fn get_moment_of_inertia(&self, mass: f32, scale: Vector) -> Vector3 {
+        if self.points.len() < 3 {
+            return Vector3::ZERO;
+        }
+        // use bad AABB approximation
+        let extents = self.compute_aabb(scale).size * 0.5;
+        Vector3::new(
+            (mass / 3.0) * (extents.y * extents.y + extents.z * extents.z),
+            (mass / 3.0) * (extents.x * extents.x + extents.z * extents.z),
+            (mass / 3.0) * (extents.x * extents.x + extents.y * extents.y),
+        )
+    }
+
+    fn allows_one_way_collision(&self) -> bool {
+        true
+    }
+
+    fn create_rapier_shape(&mut self, physics_engine: &mut PhysicsEngine) -> ShapeHandle {
+        if self.points.len() >= 3 {
+            let mut rapier_points = Vec::with_capacity(self.points.len());
+            for point in self.points.iter() {
+                rapier_points.push(vector_to_rapier(*point));
+            }
+            physics_engine.shape_create_round_convex_polyline(&rapier_points, self.border_radius)
+        } else {
+            godot_error!("RoundConvexPolygon must have at least three point");
+            ShapeHandle::default()
+        }
+    }
+
+    fn set_data(&mut self, data: Variant, physics_engine: &mut PhysicsEngine) {
+        let Ok(dictionary) = data.try_to::<Dictionary>() else {
+            godot_error!("Invalid shape data");
+            return;
+        };
+        let Some(points) = dictionary
+            .get("points")
+            .and_then(|points| points.try_to::<PackedVectorArray>().ok())
+        else {
+            godot_error!("RoundConvexPolygon data must contain points");
+            return;
+        };
+        if points.len() < 3 {
+            godot_error!("RoundConvexPolygon must have at least three point");
+            return;
+        }
+        self.points = points.to_vec();
+        self.border_radius = 0.0;
+        if let Some(border_radius) = dictionary.get("border_radius") {
+            self.border_radius = variant_to_float(&border_radius);
+        }
+        let handle = self.create_rapier_shape(physics_engine);
+        self.base
+            .set_handle(handle, self.compute_aabb(Vector::ONE), physics_engine);
+    }
+
+    fn get_data(&self) -> Variant {
+        let dictionary = dict! {
+            "points": PackedVectorArray::from(self.points.as_slice()),
+            "border_radius": self.border_radius,
+        };
+        dictionary.to_variant()
+    }
+
+    fn get_handle(&self) -> ShapeHandle {
+        self.base.get_handle()
+    }
+}
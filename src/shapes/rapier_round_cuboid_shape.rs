@@ -0,0 +1,110 @@
+#[cfg(feature = "dim2")]
+use godot::classes::physics_server_2d::*;
+#[cfg(feature = "dim3")]
+use godot::classes::physics_server_3d::*;
+use godot::prelude::*;
+
+use crate::rapier_wrapper::prelude::*;
+use crate::shapes::rapier_shape::*;
+use crate::types::*;
+// The border radius is added around the half extents
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct RapierRoundCuboidShape {
+    half_extents: Vector,
+    border_radius: real,
+    base: RapierShapeBase,
+}
+impl RapierRoundCuboidShape {
+    pub fn new(rid: Rid) -> Self {
+        Self {
+            half_extents: Vector::ZERO,
+            border_radius: 0.0,
+            base: RapierShapeBase::new(rid),
+        }
+    }
+
+    fn compute_aabb(&self) -> Rect {
+        let he = self.half_extents + Vector::ONE * self.border_radius;
+        Rect::new(-he, he * 2.0)
+    }
+}
+#[cfg_attr(feature = "serde-serialize", typetag::serde)]
+impl IRapierShape for RapierRoundCuboidShape {
+    fn get_base(&self) -> &RapierShapeBase {
+        &self.base
+    }
+
+    fn get_mut_base(&mut self) -> &mut RapierShapeBase {
+        &mut self.base
+    }
+
+    fn has_data_border_radius(&self) -> bool {
+        true
+    }
+
+    fn get_type(&self) -> ShapeType {
+        ShapeType::CUSTOM
+    }
+
+    #[cfg(feature = "dim2")]
+    fn get_moment_of_inertia(&self, mass: f32, scale: Vector) -> f32 {
+        let he2 = self.compute_aabb().size * scale;
+        mass * he2.dot(he2) / 12.0
+    }
+
+    #[cfg(feature = "dim3")]
+    fn get_moment_of_inertia(&self, mass: f32, scale: Vector) -> Vector3 {
+        let extents = self.compute_aabb().size * scale * 0.5;
+        Vector3::new(
+            (mass / 3.0) * (extents.y * extents.y + extents.z * extents.z),
+            (mass / 3.0) * (extents.x * extents.x + extents.z * extents.z),
+            (mass / 3.0) * (extents.x * extents.x + extents.y * extents.y),
+        )
+    }
+
+    fn allows_one_way_collision(&self) -> bool {
+        true
+    }
+
+    fn create_rapier_shape(&mut self, physics_engine: &mut PhysicsEngine) -> ShapeHandle {
+        let v = vector_to_rapier(self.half_extents) * 2.0;
+        physics_engine.shape_create_round_box(v, self.border_radius)
+    }
+
+    fn set_data(&mut self, data: Variant, physics_engine: &mut PhysicsEngine) {
+        let Ok(dictionary) = data.try_to::<Dictionary>() else {
+            godot_error!("Invalid shape data");
+            return;
+        };
+        let Some(half_extents) = dictionary
+            .get("half_extents")
+            .and_then(|half_extents| half_extents.try_to::<Vector>().ok())
+        else {
+            godot_error!("RoundCuboid data must contain half_extents");
+            return;
+        };
+        self.half_extents = half_extents;
+        self.border_radius = 0.0;
+        if let Some(border_radius) = dictionary.get("border_radius") {
+            self.border_radius = variant_to_float(&border_radius);
+        }
+        let handle = self.create_rapier_shape(physics_engine);
+        self.base
+            .set_handle(handle, self.compute_aabb(), physics_engine);
+    }
+
+    fn get_data(&self) -> Variant {
+        let dictionary = dict! {
+            "half_extents": self.half_extents,
+            "border_radius": self.border_radius,
+        };
+        dictionary.to_variant()
+    }
+
+    fn get_handle(&self) -> ShapeHandle {
+        self.base.get_handle()
+    }
+}
@@ -0,0 +1,85 @@
+use godot::classes::physics_server_3d::*;
+use godot::prelude::*;
+
+use crate::rapier_wrapper::prelude::*;
+use crate::shapes::rapier_shape::*;
+use crate::types::*;
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct RapierSegmentShape3D {
+    a: Vector3,
+    b: Vector3,
+    base: RapierShapeBase,
+}
+impl RapierSegmentShape3D {
+    pub fn new(rid: Rid) -> Self {
+        Self {
+            a: Vector3::ZERO,
+            b: Vector3::ZERO,
+            base: RapierShapeBase::new(rid),
+        }
+    }
+}
+#[cfg_attr(feature = "serde-serialize", typetag::serde)]
+impl IRapierShape for RapierSegmentShape3D {
+    fn get_base(&self) -> &RapierShapeBase {
+        &self.base
+    }
+
+    fn get_mut_base(&mut self) -> &mut RapierShapeBase {
+        &mut self.base
+    }
+
+    fn get_type(&self) -> ShapeType {
+        ShapeType::CUSTOM
+    }
+
+    fn get_moment_of_inertia(&self, mass: f32, scale: Vector) -> Vector3 {
+        // use bad AABB approximation
+        let extents = self.compute_aabb().size * scale * 0.5;
+        Vector3::new(
+            (mass / 3.0) * (extents.y * extents.y + extents.z * extents.z),
+            (mass / 3.0) * (extents.x * extents.x + extents.z * extents.z),
+            (mass / 3.0) * (extents.x * extents.x + extents.y * extents.y),
+        )
+    }
+
+    fn allows_one_way_collision(&self) -> bool {
+        true
+    }
+
+    fn create_rapier_shape(&mut self, physics_engine: &mut PhysicsEngine) -> ShapeHandle {
+        physics_engine.shape_create_segment(vector_to_rapier(self.a), vector_to_rapier(self.b))
+    }
+
+    fn set_data(&mut self, data: Variant, physics_engine: &mut PhysicsEngine) {
+        let Ok(points) = data.try_to::<PackedVector3Array>() else {
+            godot_error!("Invalid shape data");
+            return;
+        };
+        if points.len() != 2 {
+            godot_error!("Segment must have two points");
+            return;
+        }
+        self.a = points[0];
+        self.b = points[1];
+        let handle = self.create_rapier_shape(physics_engine);
+        self.base
+            .set_handle(handle, self.compute_aabb(), physics_engine);
+    }
+
+    fn get_data(&self) -> Variant {
+        PackedVector3Array::from([self.a, self.b].as_slice()).to_variant()
+    }
+
+    fn get_handle(&self) -> ShapeHandle {
+        self.base.get_handle()
+    }
+}
+impl RapierSegmentShape3D {
+    fn compute_aabb(&self) -> Aabb {
+        Aabb::new(self.a, Vector3::ZERO).expand(self.b)
+    }
+}
@@ -7,11 +7,20 @@ use hashbrown::HashMap;
 
 use crate::rapier_wrapper::prelude::*;
 use crate::servers::rapier_physics_server_extra::PhysicsData;
+use crate::servers::rapier_physics_server_extra::RapierShapeParam;
+use crate::shapes::rapier_voxel_shape::RapierVoxelShape;
 use crate::types::*;
 #[cfg_attr(feature = "serde-serialize", typetag::serde(tag = "type"))]
 pub trait IRapierShape {
     fn get_base(&self) -> &RapierShapeBase;
     fn get_mut_base(&mut self) -> &mut RapierShapeBase;
+    fn get_mut_voxels(&mut self) -> Option<&mut RapierVoxelShape> {
+        None
+    }
+    // Round shapes take their border radius from their data instead of the shape param
+    fn has_data_border_radius(&self) -> bool {
+        false
+    }
     fn get_type(&self) -> ShapeType;
     fn get_moment_of_inertia(&self, mass: f32, scale: Vector) -> Angle;
     fn allows_one_way_collision(&self) -> bool;
@@ -27,10 +36,24 @@ pub trait IRapierShape {
 pub struct RapierShapeBase {
     rid: Rid,
     aabb: Rect,
-    // TODO serialize this
-    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(with = "crate::types::serde_map_as_entries")
+    )]
     owners: HashMap<Rid, i32>,
     handle: ShapeHandle,
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    mesh_flags: u32,
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    border_radius: real,
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    convex_decomposition: bool,
+    // Rebuilt from the shape when it is first needed
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    decomposed_handle: ShapeHandle,
+    // One handle per chunk for shapes made of chunks, bodies create a collider for each of them
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    chunk_handles: Vec<ShapeHandle>,
 }
 impl RapierShapeBase {
     pub(super) fn new(rid: Rid) -> Self {
@@ -39,6 +62,11 @@ impl RapierShapeBase {
             aabb: Rect::default(),
             owners: HashMap::default(),
             handle: ShapeHandle::default(),
+            mesh_flags: 0,
+            border_radius: 0.0,
+            convex_decomposition: false,
+            decomposed_handle: ShapeHandle::default(),
+            chunk_handles: Vec::new(),
         }
     }
 
@@ -48,9 +76,7 @@ impl RapierShapeBase {
         aabb: Rect,
         physics_engine: &mut PhysicsEngine,
     ) {
-        if self.handle != ShapeHandle::default() {
-            self.destroy_shape(physics_engine);
-        }
+        self.destroy_handle(physics_engine);
         self.aabb = aabb;
         self.handle = handle;
     }
@@ -117,11 +143,130 @@ impl RapierShapeBase {
         self.rid
     }
 
+    // The shape has to be recreated for the params to apply
+    pub fn set_extra_param(&mut self, p_param: RapierShapeParam, p_value: Variant) {
+        match p_param {
+            RapierShapeParam::MeshFlags => {
+                if p_value.get_type() != VariantType::INT {
+                    return;
+                }
+                let mesh_flags = p_value.to::<i64>() as u32;
+                // Parry only fixes internal edges of 3D meshes
+                #[cfg(feature = "dim2")]
+                if mesh_flags & MESH_FLAG_FIX_INTERNAL_EDGES != 0 {
+                    godot_error!("Fixing internal edges is only supported in 3D");
+                    return;
+                }
+                self.mesh_flags = mesh_flags;
+            }
+            RapierShapeParam::BorderRadius => {
+                if p_value.get_type() != VariantType::FLOAT
+                    && p_value.get_type() != VariantType::INT
+                {
+                    return;
+                }
+                self.border_radius = variant_to_float(&p_value).max(0.0);
+            }
+            RapierShapeParam::ConvexDecomposition => {
+                if p_value.get_type() != VariantType::BOOL {
+                    return;
+                }
+                self.convex_decomposition = p_value.to();
+            }
+        }
+    }
+
+    pub fn get_extra_param(&self, p_param: RapierShapeParam) -> Variant {
+        match p_param {
+            RapierShapeParam::MeshFlags => (self.mesh_flags as i64).to_variant(),
+            RapierShapeParam::BorderRadius => self.border_radius.to_variant(),
+            RapierShapeParam::ConvexDecomposition => self.convex_decomposition.to_variant(),
+        }
+    }
+
+    // Only used by polyline, trimesh and heightmap shapes
+    pub fn get_mesh_flags(&self) -> u32 {
+        self.mesh_flags
+    }
+
+    // Only used by convex shapes, which are created as their round variant when it is set
+    pub fn get_border_radius(&self) -> real {
+        self.border_radius
+    }
+
+    pub fn uses_convex_decomposition(&self) -> bool {
+        self.convex_decomposition
+    }
+
+    // Dynamic bodies use the convex decomposition of concave shapes, if enabled. It is created
+    // the first time it is needed and kept until the shape changes.
+    pub fn get_solid_handle(
+        &mut self,
+        is_dynamic: bool,
+        physics_engine: &mut PhysicsEngine,
+    ) -> ShapeHandle {
+        if !is_dynamic || !self.convex_decomposition {
+            return self.handle;
+        }
+        if self.decomposed_handle == ShapeHandle::default() {
+            self.decomposed_handle = physics_engine.shape_create_convex_decomposition(self.handle);
+        }
+        if self.decomposed_handle == ShapeHandle::default() {
+            return self.handle;
+        }
+        self.decomposed_handle
+    }
+
+    pub fn get_chunk_handles(&self) -> &[ShapeHandle] {
+        &self.chunk_handles
+    }
+
+    pub(super) fn set_chunk_handles(
+        &mut self,
+        chunk_handles: Vec<ShapeHandle>,
+        physics_engine: &mut PhysicsEngine,
+    ) {
+        self.destroy_chunk_handles(physics_engine);
+        self.chunk_handles = chunk_handles;
+    }
+
+    pub(super) fn set_chunk_handle(
+        &mut self,
+        chunk_index: usize,
+        chunk_handle: ShapeHandle,
+        physics_engine: &mut PhysicsEngine,
+    ) {
+        let Some(previous_handle) = self.chunk_handles.get_mut(chunk_index) else {
+            return;
+        };
+        if *previous_handle != ShapeHandle::default() {
+            physics_engine.shape_destroy(*previous_handle);
+        }
+        *previous_handle = chunk_handle;
+    }
+
     pub fn destroy_shape(&mut self, physics_engine: &mut PhysicsEngine) {
+        self.destroy_handle(physics_engine);
+        self.destroy_chunk_handles(physics_engine);
+    }
+
+    fn destroy_chunk_handles(&mut self, physics_engine: &mut PhysicsEngine) {
+        for chunk_handle in self.chunk_handles.drain(..) {
+            if chunk_handle != ShapeHandle::default() {
+                physics_engine.shape_destroy(chunk_handle);
+            }
+        }
+    }
+
+    fn destroy_handle(&mut self, physics_engine: &mut PhysicsEngine) {
         if self.handle != ShapeHandle::default() {
             physics_engine.shape_destroy(self.handle);
             self.handle = ShapeHandle::default();
         }
+        if self.decomposed_handle != ShapeHandle::default() {
+            physics_engine.shape_destroy(self.decomposed_handle);
+            self.decomposed_handle = ShapeHandle::default();
+        }
     }
 }
 impl Drop for RapierShapeBase {
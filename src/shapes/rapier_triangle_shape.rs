@@ -0,0 +1,102 @@
+#[cfg(feature = "dim2")]
+use godot::classes::physics_server_2d::*;
+#[cfg(feature = "dim3")]
+use godot::classes::physics_server_3d::*;
+use godot::prelude::*;
+
+use crate::rapier_wrapper::prelude::*;
+use crate::shapes::rapier_shape::*;
+use crate::types::*;
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct RapierTriangleShape {
+    points: [Vector; 3],
+    base: RapierShapeBase,
+}
+impl RapierTriangleShape {
+    pub fn new(rid: Rid) -> Self {
+        Self {
+            points: [Vector::ZERO; 3],
+            base: RapierShapeBase::new(rid),
+        }
+    }
+
+    fn compute_aabb(&self, scale: Vector) -> Rect {
+        let mut aabb_new = Rect::new(self.points[0] * scale, Vector::ZERO);
+        for point in self.points.iter() {
+            aabb_new = aabb_new.expand(*point * scale);
+        }
+        aabb_new.grow(self.base.get_border_radius())
+    }
+}
+#[cfg_attr(feature = "serde-serialize", typetag::serde)]
+impl IRapierShape for RapierTriangleShape {
+    fn get_base(&self) -> &RapierShapeBase {
+        &self.base
+    }
+
+    fn get_mut_base(&mut self) -> &mut RapierShapeBase {
+        &mut self.base
+    }
+
+    fn get_type(&self) -> ShapeType {
+        ShapeType::CUSTOM
+    }
+
+    #[cfg(feature = "dim2")]
+    fn get_moment_of_inertia(&self, mass: f32, scale: Vector) -> f32 {
+        let aabb_new = self.compute_aabb(scale);
+        mass * aabb_new.size.dot(aabb_new.size) / 12.0
+    }
+
+    #[cfg(feature = "dim3")]
+    fn get_moment_of_inertia(&self, mass: f32, scale: Vector) -> Vector3 {
+        // use bad AABB approximation
+        let extents = self.compute_aabb(scale).size * 0.5;
+        Vector3::new(
+            (mass / 3.0) * (extents.y * extents.y + extents.z * extents.z),
+            (mass / 3.0) * (extents.x * extents.x + extents.z * extents.z),
+            (mass / 3.0) * (extents.x * extents.x + extents.y * extents.y),
+        )
+    }
+
+    fn allows_one_way_collision(&self) -> bool {
+        true
+    }
+
+    fn create_rapier_shape(&mut self, physics_engine: &mut PhysicsEngine) -> ShapeHandle {
+        let a = vector_to_rapier(self.points[0]);
+        let b = vector_to_rapier(self.points[1]);
+        let c = vector_to_rapier(self.points[2]);
+        let border_radius = self.base.get_border_radius();
+        if border_radius > 0.0 {
+            return physics_engine.shape_create_round_triangle(a, b, c, border_radius);
+        }
+        physics_engine.shape_create_triangle(a, b, c)
+    }
+
+    fn set_data(&mut self, data: Variant, physics_engine: &mut PhysicsEngine) {
+        let Ok(points) = data.try_to::<PackedVectorArray>() else {
+            godot_error!("Invalid shape data");
+            return;
+        };
+        if points.len() != 3 {
+            godot_error!("Triangle must have three points");
+            return;
+        }
+        self.points = [points[0], points[1], points[2]];
+        let handle = self.create_rapier_shape(physics_engine);
+        self.base
+            .set_handle(handle, self.compute_aabb(Vector::ONE), physics_engine);
+    }
+
+    fn get_data(&self) -> Variant {
+        PackedVectorArray::from(self.points.as_slice()).to_variant()
+    }
+
+    fn get_handle(&self) -> ShapeHandle {
+        self.base.get_handle()
+    }
+}
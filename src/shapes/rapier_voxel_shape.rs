@@ -0,0 +1,458 @@
+#[cfg(feature = "dim2")]
+use godot::classes::physics_server_2d::*;
+#[cfg(feature = "dim3")]
+use godot::classes::physics_server_3d::*;
+use godot::prelude::*;
+
+use crate::rapier_wrapper::logging::physics_error;
+use crate::rapier_wrapper::prelude::*;
+use crate::shapes::rapier_shape::*;
+use crate::types::*;
+type RapierVector = rapier::math::Vector<rapier::math::Real>;
+// Number of cells per axis merged together, an update only merges the chunks it touches
+const CHUNK_SIZE: i32 = 16;
+#[cfg(feature = "dim2")]
+fn cell_from_vector(cell: Vectori) -> [i32; 3] {
+    [cell.x, cell.y, 0]
+}
+#[cfg(feature = "dim3")]
+fn cell_from_vector(cell: Vectori) -> [i32; 3] {
+    [cell.x, cell.y, cell.z]
+}
+#[cfg(feature = "dim2")]
+fn cell_to_vector(cell: [i32; 3]) -> Vectori {
+    Vectori::new(cell[0], cell[1])
+}
+#[cfg(feature = "dim3")]
+fn cell_to_vector(cell: [i32; 3]) -> Vectori {
+    Vectori::new(cell[0], cell[1], cell[2])
+}
+// Box of occupied cells, from min included to max excluded
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[derive(Clone, Copy)]
+struct VoxelBox {
+    min: [i32; 3],
+    max: [i32; 3],
+}
+// Grid of cells starting at the shape origin, stored as one byte per cell with x changing fastest.
+// Occupied cells are merged into boxes, with one compound per chunk used by the body colliders.
+// Queries and areas use a single compound of all the boxes, made of the chunk sub shapes, so an
+// update only creates the boxes of the chunks it touches.
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct RapierVoxelShape {
+    size: [i32; 3],
+    cell_size: Vector,
+    cells: Vec<u8>,
+    chunk_boxes: Vec<Vec<VoxelBox>>,
+    base: RapierShapeBase,
+}
+impl RapierVoxelShape {
+    pub fn new(rid: Rid) -> Self {
+        Self {
+            size: [0, 0, 1],
+            cell_size: Vector::ONE,
+            cells: Vec::new(),
+            chunk_boxes: Vec::new(),
+            base: RapierShapeBase::new(rid),
+        }
+    }
+
+    // Sets the given cells as occupied or empty, merging again only the chunks that changed.
+    // Returns the indexes of these chunks.
+    pub fn set_cells(
+        &mut self,
+        cells: &[Vectori],
+        occupied: bool,
+        physics_engine: &mut PhysicsEngine,
+    ) -> Vec<usize> {
+        let mut dirty_chunks = Vec::new();
+        for cell in cells {
+            let cell = cell_from_vector(*cell);
+            let Some(index) = self.cell_index(cell) else {
+                physics_error!(
+                    "Cell {:?} is outside of the voxel grid",
+                    cell_to_vector(cell)
+                );
+                continue;
+            };
+            if (self.cells[index] != 0) == occupied {
+                continue;
+            }
+            self.cells[index] = occupied as u8;
+            let chunk_index = self.chunk_index(cell);
+            if !dirty_chunks.contains(&chunk_index) {
+                dirty_chunks.push(chunk_index);
+            }
+        }
+        if dirty_chunks.is_empty() {
+            return dirty_chunks;
+        }
+        for chunk_index in dirty_chunks.iter() {
+            self.chunk_boxes[*chunk_index] = self.merge_chunk(*chunk_index);
+            let chunk_handle = self.create_chunk_shape(*chunk_index, physics_engine);
+            self.base
+                .set_chunk_handle(*chunk_index, chunk_handle, physics_engine);
+        }
+        let handle = self.create_rapier_shape(physics_engine);
+        self.base
+            .set_handle(handle, self.compute_aabb(), physics_engine);
+        dirty_chunks
+    }
+
+    fn create_chunk_shape(
+        &self,
+        chunk_index: usize,
+        physics_engine: &mut PhysicsEngine,
+    ) -> ShapeHandle {
+        let boxes: Vec<(RapierVector, RapierVector)> = self.chunk_boxes[chunk_index]
+            .iter()
+            .map(|voxel_box| self.box_to_rapier(voxel_box))
+            .collect();
+        physics_engine.shape_create_box_compound(&boxes)
+    }
+
+    fn cell_index(&self, cell: [i32; 3]) -> Option<usize> {
+        for axis in 0..3 {
+            if cell[axis] < 0 || cell[axis] >= self.size[axis] {
+                return None;
+            }
+        }
+        Some((cell[0] + self.size[0] * (cell[1] + self.size[1] * cell[2])) as usize)
+    }
+
+    fn is_occupied(&self, cell: [i32; 3]) -> bool {
+        self.cell_index(cell)
+            .is_some_and(|index| self.cells[index] != 0)
+    }
+
+    fn chunk_counts(&self) -> [i32; 3] {
+        self.size.map(|size| (size + CHUNK_SIZE - 1) / CHUNK_SIZE)
+    }
+
+    fn chunk_index(&self, cell: [i32; 3]) -> usize {
+        let chunk_counts = self.chunk_counts();
+        let chunk = cell.map(|cell| cell / CHUNK_SIZE);
+        (chunk[0] + chunk_counts[0] * (chunk[1] + chunk_counts[1] * chunk[2])) as usize
+    }
+
+    // Greedily merges the occupied cells of a chunk, first along x, then y, then z
+    fn merge_chunk(&self, chunk_index: usize) -> Vec<VoxelBox> {
+        let chunk_counts = self.chunk_counts();
+        let chunk_index = chunk_index as i32;
+        let chunk = [
+            chunk_index % chunk_counts[0],
+            (chunk_index / chunk_counts[0]) % chunk_counts[1],
+            chunk_index / (chunk_counts[0] * chunk_counts[1]),
+        ];
+        let min = chunk.map(|chunk| chunk * CHUNK_SIZE);
+        let mut max = [0; 3];
+        for axis in 0..3 {
+            max[axis] = (min[axis] + CHUNK_SIZE).min(self.size[axis]);
+        }
+        let chunk_size = [max[0] - min[0], max[1] - min[1], max[2] - min[2]];
+        let mut visited = vec![false; (chunk_size[0] * chunk_size[1] * chunk_size[2]) as usize];
+        let visited_index = |cell: [i32; 3]| {
+            ((cell[0] - min[0])
+                + chunk_size[0] * ((cell[1] - min[1]) + chunk_size[1] * (cell[2] - min[2])))
+                as usize
+        };
+        let is_free = |visited: &Vec<bool>, cell: [i32; 3]| {
+            self.is_occupied(cell) && !visited[visited_index(cell)]
+        };
+        let mut boxes = Vec::new();
+        for z in min[2]..max[2] {
+            for y in min[1]..max[1] {
+                for x in min[0]..max[0] {
+                    if !is_free(&visited, [x, y, z]) {
+                        continue;
+                    }
+                    let mut end_x = x + 1;
+                    while end_x < max[0] && is_free(&visited, [end_x, y, z]) {
+                        end_x += 1;
+                    }
+                    let mut end_y = y + 1;
+                    while end_y < max[1]
+                        && (x..end_x).all(|cell_x| is_free(&visited, [cell_x, end_y, z]))
+                    {
+                        end_y += 1;
+                    }
+                    let mut end_z = z + 1;
+                    while end_z < max[2]
+                        && (y..end_y).all(|cell_y| {
+                            (x..end_x).all(|cell_x| is_free(&visited, [cell_x, cell_y, end_z]))
+                        })
+                    {
+                        end_z += 1;
+                    }
+                    for cell_z in z..end_z {
+                        for cell_y in y..end_y {
+                            for cell_x in x..end_x {
+                                visited[visited_index([cell_x, cell_y, cell_z])] = true;
+                            }
+                        }
+                    }
+                    boxes.push(VoxelBox {
+                        min: [x, y, z],
+                        max: [end_x, end_y, end_z],
+                    });
+                }
+            }
+        }
+        boxes
+    }
+
+    fn merge_all_chunks(&mut self) {
+        let chunk_counts = self.chunk_counts();
+        let chunk_count = (chunk_counts[0] * chunk_counts[1] * chunk_counts[2]) as usize;
+        self.chunk_boxes = (0..chunk_count)
+            .map(|chunk_index| self.merge_chunk(chunk_index))
+            .collect();
+    }
+
+    #[cfg(feature = "dim2")]
+    fn box_to_rapier(&self, voxel_box: &VoxelBox) -> (RapierVector, RapierVector) {
+        let min = Vector::new(voxel_box.min[0] as real, voxel_box.min[1] as real) * self.cell_size;
+        let max = Vector::new(voxel_box.max[0] as real, voxel_box.max[1] as real) * self.cell_size;
+        (
+            vector_to_rapier((min + max) * 0.5),
+            vector_to_rapier((max - min) * 0.5),
+        )
+    }
+
+    #[cfg(feature = "dim3")]
+    fn box_to_rapier(&self, voxel_box: &VoxelBox) -> (RapierVector, RapierVector) {
+        let min = Vector::new(
+            voxel_box.min[0] as real,
+            voxel_box.min[1] as real,
+            voxel_box.min[2] as real,
+        ) * self.cell_size;
+        let max = Vector::new(
+            voxel_box.max[0] as real,
+            voxel_box.max[1] as real,
+            voxel_box.max[2] as real,
+        ) * self.cell_size;
+        (
+            vector_to_rapier((min + max) * 0.5),
+            vector_to_rapier((max - min) * 0.5),
+        )
+    }
+
+    #[cfg(feature = "dim2")]
+    fn compute_aabb(&self) -> Rect {
+        let size = Vector::new(self.size[0] as real, self.size[1] as real);
+        Rect::new(Vector::ZERO, size * self.cell_size)
+    }
+
+    #[cfg(feature = "dim3")]
+    fn compute_aabb(&self) -> Rect {
+        let size = Vector::new(
+            self.size[0] as real,
+            self.size[1] as real,
+            self.size[2] as real,
+        );
+        Rect::new(Vector::ZERO, size * self.cell_size)
+    }
+}
+#[cfg_attr(feature = "serde-serialize", typetag::serde)]
+impl IRapierShape for RapierVoxelShape {
+    fn get_base(&self) -> &RapierShapeBase {
+        &self.base
+    }
+
+    fn get_mut_base(&mut self) -> &mut RapierShapeBase {
+        &mut self.base
+    }
+
+    fn get_mut_voxels(&mut self) -> Option<&mut RapierVoxelShape> {
+        Some(self)
+    }
+
+    fn get_type(&self) -> ShapeType {
+        ShapeType::CUSTOM
+    }
+
+    #[cfg(feature = "dim2")]
+    fn get_moment_of_inertia(&self, mass: f32, scale: Vector) -> f32 {
+        let size = self.compute_aabb().size * scale;
+        mass * size.dot(size) / 12.0
+    }
+
+    #[cfg(feature = "dim3")]
+    fn get_moment_of_inertia(&self, mass: f32, scale: Vector) -> Vector3 {
+        // use bad AABB approximation
+        let extents = self.compute_aabb().size * scale * 0.5;
+        Vector3::new(
+            (mass / 3.0) * (extents.y * extents.y + extents.z * extents.z),
+            (mass / 3.0) * (extents.x * extents.x + extents.z * extents.z),
+            (mass / 3.0) * (extents.x * extents.x + extents.y * extents.y),
+        )
+    }
+
+    fn allows_one_way_collision(&self) -> bool {
+        true
+    }
+
+    // Uses the chunk shapes, so they have to be created first
+    fn create_rapier_shape(&mut self, physics_engine: &mut PhysicsEngine) -> ShapeHandle {
+        physics_engine.shape_create_merged_compound(self.base.get_chunk_handles())
+    }
+
+    fn set_data(&mut self, data: Variant, physics_engine: &mut PhysicsEngine) {
+        let Ok(dictionary) = data.try_to::<Dictionary>() else {
+            godot_error!("Invalid shape data");
+            return;
+        };
+        let Some(size) = dictionary
+            .get("size")
+            .and_then(|size| size.try_to::<Vectori>().ok())
+        else {
+            godot_error!("Voxels data must contain size");
+            return;
+        };
+        let size = cell_from_vector(size);
+        #[cfg(feature = "dim2")]
+        let size = [size[0], size[1], 1];
+        if size.iter().any(|size| *size < 1) {
+            godot_error!("Voxels size must be at least one cell");
+            return;
+        }
+        let Some(cell_count) = size[0]
+            .checked_mul(size[1])
+            .and_then(|cell_count| cell_count.checked_mul(size[2]))
+        else {
+            godot_error!("Voxels size is too large");
+            return;
+        };
+        let cell_count = cell_count as usize;
+        let cells = match dictionary.get("cells") {
+            Some(cells) => {
+                let Ok(cells) = cells.try_to::<PackedByteArray>() else {
+                    godot_error!("Voxels cells must be a PackedByteArray");
+                    return;
+                };
+                cells.to_vec()
+            }
+            None => vec![0; cell_count],
+        };
+        if cells.len() != cell_count {
+            godot_error!(
+                "Voxels cells has {} cells, expected {}",
+                cells.len(),
+                cell_count
+            );
+            return;
+        }
+        if let Some(cell_size) = dictionary.get("cell_size") {
+            let Ok(cell_size) = cell_size.try_to::<Vector>() else {
+                godot_error!("Voxels cell_size must be a vector");
+                return;
+            };
+            self.cell_size = cell_size;
+        }
+        self.size = size;
+        self.cells = cells;
+        self.merge_all_chunks();
+        let chunk_handles = (0..self.chunk_boxes.len())
+            .map(|chunk_index| self.create_chunk_shape(chunk_index, physics_engine))
+            .collect();
+        self.base.set_chunk_handles(chunk_handles, physics_engine);
+        let handle = self.create_rapier_shape(physics_engine);
+        self.base
+            .set_handle(handle, self.compute_aabb(), physics_engine);
+    }
+
+    fn get_data(&self) -> Variant {
+        let dictionary = dict! {
+            "size": cell_to_vector(self.size),
+            "cell_size": self.cell_size,
+            "cells": PackedByteArray::from(self.cells.as_slice()),
+        };
+        dictionary.to_variant()
+    }
+
+    fn get_handle(&self) -> ShapeHandle {
+        self.base.get_handle()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn voxel_shape(size: [i32; 3], occupied: &[[i32; 3]]) -> RapierVoxelShape {
+        let mut shape = RapierVoxelShape::new(Rid::Invalid);
+        shape.size = size;
+        shape.cells = vec![0; (size[0] * size[1] * size[2]) as usize];
+        for cell in occupied {
+            let index = shape.cell_index(*cell).unwrap();
+            shape.cells[index] = 1;
+        }
+        shape
+    }
+    fn box_bounds(boxes: &[VoxelBox]) -> Vec<([i32; 3], [i32; 3])> {
+        boxes
+            .iter()
+            .map(|voxel_box| (voxel_box.min, voxel_box.max))
+            .collect()
+    }
+    #[test]
+    fn merge_chunk_empty() {
+        let shape = voxel_shape([4, 4, 1], &[]);
+        assert!(shape.merge_chunk(0).is_empty());
+    }
+    #[test]
+    fn merge_chunk_full_grid_is_one_box() {
+        let mut occupied = Vec::new();
+        for z in 0..2 {
+            for y in 0..2 {
+                for x in 0..3 {
+                    occupied.push([x, y, z]);
+                }
+            }
+        }
+        let shape = voxel_shape([3, 2, 2], &occupied);
+        assert_eq!(
+            box_bounds(&shape.merge_chunk(0)),
+            vec![([0, 0, 0], [3, 2, 2])]
+        );
+    }
+    #[test]
+    fn merge_chunk_merges_along_x_first() {
+        let shape = voxel_shape([2, 2, 1], &[[0, 0, 0], [1, 0, 0], [0, 1, 0]]);
+        assert_eq!(
+            box_bounds(&shape.merge_chunk(0)),
+            vec![([0, 0, 0], [2, 1, 1]), ([0, 1, 0], [1, 2, 1])]
+        );
+    }
+    #[test]
+    fn merge_chunk_stops_at_chunk_border() {
+        let occupied: Vec<[i32; 3]> = (0..20).map(|x| [x, 0, 0]).collect();
+        let shape = voxel_shape([20, 1, 1], &occupied);
+        assert_eq!(shape.chunk_index([15, 0, 0]), 0);
+        assert_eq!(shape.chunk_index([16, 0, 0]), 1);
+        assert_eq!(
+            box_bounds(&shape.merge_chunk(0)),
+            vec![([0, 0, 0], [16, 1, 1])]
+        );
+        assert_eq!(
+            box_bounds(&shape.merge_chunk(1)),
+            vec![([16, 0, 0], [20, 1, 1])]
+        );
+    }
+    #[test]
+    fn merge_chunk_only_merges_its_cells() {
+        let shape = voxel_shape([20, 20, 1], &[[1, 1, 0], [17, 18, 0]]);
+        let chunk_index = shape.chunk_index([17, 18, 0]);
+        assert_eq!(chunk_index, 3);
+        assert_eq!(
+            box_bounds(&shape.merge_chunk(chunk_index)),
+            vec![([17, 18, 0], [18, 19, 1])]
+        );
+        assert!(shape.merge_chunk(1).is_empty());
+        assert!(shape.merge_chunk(2).is_empty());
+    }
+}
@@ -3,6 +3,8 @@ use godot::classes::*;
 use godot::prelude::*;
 
 use super::rapier_direct_space_state_impl::RapierDirectSpaceStateImpl;
+#[cfg(feature = "serde-serialize")]
+use super::rapier_space::RapierSpace;
 use crate::servers::RapierPhysicsServer;
 use crate::types::PhysicsServer;
 #[derive(GodotClass)]
@@ -32,7 +34,31 @@ impl RapierDirectSpaceState2D {
         let Some(space) = physics_data.spaces.get(&self.space) else {
             return "{}".to_string();
         };
-        space.export_json(&mut physics_data.physics_engine)
+        space.export_json(&mut physics_data.physics_engine, &physics_data.fluids)
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    #[func]
+    pub fn import_json(&self, data: String) {
+        let Ok(mut physics_singleton) =
+            PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
+        else {
+            return;
+        };
+        let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
+        RapierSpace::import_json(&self.space, physics_data, data);
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    #[func]
+    pub fn import_binary(&self, data: PackedByteArray) {
+        let Ok(mut physics_singleton) =
+            PhysicsServer::singleton().try_cast::<RapierPhysicsServer>()
+        else {
+            return;
+        };
+        let physics_data = &mut physics_singleton.bind_mut().implementation.physics_data;
+        RapierSpace::import_binary(&self.space, physics_data, data);
     }
 }
 #[godot_api]
@@ -75,6 +75,10 @@ impl RapierDirectSpaceStateImpl {
             result.rid = rid;
             result.shape = shape_index as i32;
             if let Some(collision_object_2d) = physics_data.collision_objects.get(&result.rid) {
+                result.shape = collision_object_2d
+                    .get_base()
+                    .get_shape_index_at(shape_index, result.position)
+                    as i32;
                 let instance_id = collision_object_2d.get_base().get_instance_id();
                 result.collider_id = ObjectId { id: instance_id };
                 if instance_id != 0 {
@@ -144,6 +148,10 @@ impl RapierDirectSpaceStateImpl {
             results_slice[i].shape = shape_index as i32;
             let collision_object_2d = physics_data.collision_objects.get(&rid);
             if let Some(collision_object_2d) = collision_object_2d {
+                results_slice[i].shape = collision_object_2d
+                    .get_base()
+                    .get_shape_index_at(shape_index, position)
+                    as i32;
                 let instance_id = collision_object_2d.get_base().get_instance_id();
                 results_slice[i].collider_id = ObjectId { id: instance_id };
                 if instance_id != 0 {
@@ -214,7 +222,10 @@ impl RapierDirectSpaceStateImpl {
             let (rid, shape_index) =
                 RapierCollisionObject::get_collider_user_data(&result.user_data);
             if let Some(collision_object_2d) = physics_data.collision_objects.get(&rid) {
-                results_slice[cpt].shape = shape_index as i32;
+                results_slice[cpt].shape = collision_object_2d
+                    .get_base()
+                    .get_shape_index_at(shape_index, vector_to_godot(result.pixel_witness1))
+                    as i32;
                 results_slice[cpt].rid = rid;
                 let instance_id = collision_object_2d.get_base().get_instance_id();
                 results_slice[cpt].collider_id = ObjectId { id: instance_id };
@@ -396,8 +407,10 @@ impl RapierDirectSpaceStateImpl {
             }
             r_info.normal = vector_to_godot(result.normal1);
             r_info.rid = rid;
-            r_info.shape = shape_index as i32;
             r_info.point = vector_to_godot(result.pixel_witness1);
+            r_info.shape = collision_object_2d
+                .get_base()
+                .get_shape_index_at(shape_index, r_info.point) as i32;
         }
         true
     }
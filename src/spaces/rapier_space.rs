@@ -8,13 +8,21 @@ use godot::engine::physics_server_3d::*;
 use godot::prelude::*;
 use hashbrown::HashMap;
 use hashbrown::HashSet;
+#[cfg(feature = "serde-serialize")]
+use rapier::dynamics::ImpulseJointHandle;
 use rapier::geometry::ColliderHandle;
+#[cfg(feature = "serde-serialize")]
+use servers::rapier_physics_server_export::*;
 use servers::rapier_physics_server_extra::PhysicsCollisionObjects;
 use servers::rapier_physics_server_extra::PhysicsData;
+#[cfg(feature = "serde-serialize")]
+use servers::rapier_physics_server_extra::PhysicsFluids;
 
 use super::PhysicsDirectSpaceState;
 use super::RapierDirectSpaceState;
 use crate::bodies::rapier_collision_object::*;
+use crate::fluids::rapier_fluid::RapierFluid;
+use crate::rapier_wrapper::fluid::FluidExport;
 use crate::rapier_wrapper::prelude::*;
 use crate::servers::rapier_project_settings::*;
 use crate::types::*;
@@ -23,6 +31,16 @@ use crate::*;
 pub struct SpaceExport<'a> {
     pub inner: &'a PhysicsObjects,
     pub space: &'a RapierSpace,
+    pub liquid_world: Vec<FluidExport>,
+    pub fluids: Vec<&'a RapierFluid>,
+    pub settings: SpaceSettingsExport,
+}
+#[cfg_attr(feature = "serde-serialize", derive(serde::Deserialize))]
+pub struct SpaceImport {
+    pub inner: PhysicsObjects,
+    pub space: RapierSpace,
+    pub liquid_world: Vec<FluidExport>,
+    pub fluids: Vec<RapierFluid>,
 }
 #[cfg_attr(
     feature = "serde-serialize",
@@ -62,10 +80,14 @@ const DEFAULT_GRAVITY: &str = "physics/3d/default_gravity";
     derive(serde::Serialize, serde::Deserialize)
 )]
 pub struct RapierSpace {
+    rid: Rid,
     #[cfg_attr(feature = "serde-serialize", serde(skip))]
     direct_access: Option<Gd<PhysicsDirectSpaceState>>,
     handle: WorldHandle,
     removed_colliders: HashMap<ColliderHandle, RemovedColliderInfo>,
+    // Shapes reported for the merged colliders overlapping areas, by area and merged collider
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    merged_overlap_shapes: HashMap<(ColliderHandle, ColliderHandle), usize>,
     active_list: HashSet<Rid>,
     mass_properties_update_list: HashSet<Rid>,
     gravity_update_list: HashSet<Rid>,
@@ -105,9 +127,11 @@ impl RapierSpace {
             .get_setting_with_override(DEFAULT_GRAVITY.into())
             .to();
         Self {
+            rid,
             direct_access: Some(direct_access.upcast()),
             handle,
             removed_colliders: HashMap::default(),
+            merged_overlap_shapes: HashMap::default(),
             active_list: HashSet::default(),
             mass_properties_update_list: HashSet::default(),
             gravity_update_list: HashSet::default(),
@@ -129,6 +153,10 @@ impl RapierSpace {
         }
     }
 
+    pub fn get_rid(&self) -> Rid {
+        self.rid
+    }
+
     pub fn get_handle(&self) -> WorldHandle {
         self.handle
     }
@@ -210,6 +238,25 @@ impl RapierSpace {
         self.removed_colliders.get(handle)
     }
 
+    pub fn add_merged_overlap_shape(
+        &mut self,
+        area_collider: ColliderHandle,
+        merged_collider: ColliderHandle,
+        shape_index: usize,
+    ) {
+        self.merged_overlap_shapes
+            .insert((area_collider, merged_collider), shape_index);
+    }
+
+    pub fn take_merged_overlap_shape(
+        &mut self,
+        area_collider: ColliderHandle,
+        merged_collider: ColliderHandle,
+    ) -> Option<usize> {
+        self.merged_overlap_shapes
+            .remove(&(area_collider, merged_collider))
+    }
+
     pub fn get_queries(
         &mut self,
         physics_data_collision_objects: &mut HashMap<Rid, Box<dyn IRapierCollisionObject>>,
@@ -457,36 +504,291 @@ impl RapierSpace {
     }
 
     #[cfg(feature = "serde-serialize")]
-    pub fn export_json(&self, physics_engine: &mut PhysicsEngine) -> String {
+    pub fn export_json(
+        &self,
+        physics_engine: &mut PhysicsEngine,
+        fluids: &PhysicsFluids,
+    ) -> String {
+        let liquid_world = physics_engine.world_export_fluids(self.handle);
+        if let Some(inner) = physics_engine.world_export(self.handle) {
+            let space_export = SpaceExport {
+                inner,
+                space: self,
+                liquid_world,
+                fluids: self.get_fluids(fluids),
+                settings: self.get_export_settings(),
+            };
+            return export_json(ExportKind::Space, &space_export);
+        }
+        "{}".to_string()
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    pub fn export_binary(
+        &self,
+        physics_engine: &mut PhysicsEngine,
+        fluids: &PhysicsFluids,
+    ) -> PackedByteArray {
+        let liquid_world = physics_engine.world_export_fluids(self.handle);
         if let Some(inner) = physics_engine.world_export(self.handle) {
-            let space_export = SpaceExport { inner, space: self };
-            match serde_json::to_string_pretty(&space_export) {
-                Ok(s) => return s,
+            let space_export = SpaceExport {
+                inner,
+                space: self,
+                liquid_world,
+                fluids: self.get_fluids(fluids),
+                settings: self.get_export_settings(),
+            };
+            return export_binary(ExportKind::Space, &space_export);
+        }
+        PackedByteArray::new()
+    }
+
+    // Same values the server steps the space with
+    #[cfg(feature = "serde-serialize")]
+    fn get_export_settings(&self) -> SpaceSettingsExport {
+        SpaceSettingsExport {
+            length_unit: RapierProjectSettings::get_length_unit(),
+            num_solver_iterations: RapierProjectSettings::get_solver_num_solver_iterations()
+                as usize,
+            num_additional_friction_iterations:
+                RapierProjectSettings::get_solver_num_additional_friction_iterations() as usize,
+            num_internal_pgs_iterations:
+                RapierProjectSettings::get_solver_num_internal_pgs_iterations() as usize,
+            max_ccd_substeps: RapierProjectSettings::get_solver_max_ccd_substeps() as usize,
+            pixel_gravity: vector_to_rapier(self.default_gravity_dir) * self.default_gravity_value,
+            fluid_particle_radius: RapierProjectSettings::get_fluid_particle_radius(),
+            fluid_smoothing_factor: RapierProjectSettings::get_fluid_smoothing_factor(),
+        }
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    fn get_fluids<'a>(&self, fluids: &'a PhysicsFluids) -> Vec<&'a RapierFluid> {
+        fluids
+            .values()
+            .filter(|fluid| fluid.get_space() == self.rid)
+            .collect()
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    pub fn export_delta(
+        &self,
+        physics_engine: &mut PhysicsEngine,
+        consumer_id: u64,
+        baseline_id: u64,
+    ) -> PackedByteArray {
+        if let Some(delta) =
+            physics_engine.world_export_delta(self.handle, consumer_id, baseline_id)
+        {
+            match bincode::serialize(&delta) {
+                Ok(binary_data) => return PackedByteArray::from(binary_data.as_slice()),
                 Err(e) => {
-                    godot_error!("Failed to serialize space: {}", e);
+                    godot_error!("Failed to serialize space delta: {}", e);
                 }
             }
         }
-        "{}".to_string()
+        PackedByteArray::new()
     }
 
+    // Returns the id of the applied delta, to be used as baseline for the next one
     #[cfg(feature = "serde-serialize")]
-    pub fn export_binary(&self, physics_engine: &mut PhysicsEngine) -> PackedByteArray {
-        let mut buf = PackedByteArray::new();
-        if let Some(inner) = physics_engine.world_export(self.handle) {
-            let space_export = SpaceExport { inner, space: self };
-            let binary_data = bincode::serialize(&space_export);
-            if binary_data.is_err() {
-                godot_error!("Failed to serialize space: {}", binary_data.err().unwrap());
-            } else {
-                let binary_data = binary_data.unwrap();
-                buf.resize(binary_data.len());
-                for i in 0..binary_data.len() {
-                    buf[i] = binary_data[i];
+    pub fn apply_delta(&self, physics_engine: &mut PhysicsEngine, data: PackedByteArray) -> u64 {
+        match bincode::deserialize::<WorldDelta>(data.as_slice()) {
+            Ok(delta) => {
+                let structural_changes = physics_engine.world_apply_delta(self.handle, &delta);
+                if structural_changes > 0 {
+                    godot_error!(
+                        "Space delta adds or removes {} objects, it was not applied. Import a full export of the space instead",
+                        structural_changes
+                    );
+                    return 0;
                 }
+                delta.id
+            }
+            Err(e) => {
+                godot_error!("Failed to deserialize space delta: {}", e);
+                0
             }
         }
-        buf
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    pub fn import_json(space_rid: &Rid, physics_data: &mut PhysicsData, data: String) {
+        match import_json::<SpaceImport>(ExportKind::Space, &data) {
+            Ok(space_import) => RapierSpace::import(space_rid, physics_data, space_import),
+            Err(e) => {
+                godot_error!("Failed to deserialize space: {}", e);
+            }
+        }
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    pub fn import_binary(space_rid: &Rid, physics_data: &mut PhysicsData, data: PackedByteArray) {
+        match import_binary::<SpaceImport>(ExportKind::Space, data.as_slice()) {
+            Ok(space_import) => RapierSpace::import(space_rid, physics_data, space_import),
+            Err(e) => {
+                godot_error!("Failed to deserialize space: {}", e);
+            }
+        }
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    fn import(space_rid: &Rid, physics_data: &mut PhysicsData, space_import: SpaceImport) {
+        let SpaceImport {
+            inner,
+            space: mut imported_space,
+            liquid_world,
+            fluids,
+        } = space_import;
+        imported_space.detach_world();
+        let Some(space) = physics_data.spaces.get_mut(space_rid) else {
+            godot_error!("Space not found for import");
+            return;
+        };
+        space.set_imported_state(&mut imported_space);
+        let space_handle = space.get_handle();
+        let (joint_handles, dropped_objects) =
+            RapierSpace::import_world(space_handle, physics_data, inner);
+        if dropped_objects > 0 {
+            godot_error!(
+                "Space import has {} bodies and colliders whose objects don't exist in this server, they were dropped",
+                dropped_objects
+            );
+        }
+        for joint in physics_data.joints.values_mut() {
+            let joint = joint.get_mut_base();
+            if joint.get_space_handle() != space_handle {
+                continue;
+            }
+            match joint_handles.get(&joint.get_handle()) {
+                Some(handle) => joint.set_handle(*handle),
+                None => joint.set_handle(ImpulseJointHandle::invalid()),
+            }
+        }
+        let fluid_handles = physics_data
+            .physics_engine
+            .world_import_fluids(space_handle, liquid_world);
+        // Fluids missing from the state were emptied in the world
+        for fluid in physics_data.fluids.values_mut() {
+            if fluid.get_space() == *space_rid
+                && !fluids
+                    .iter()
+                    .any(|imported_fluid| imported_fluid.get_rid() == fluid.get_rid())
+            {
+                fluid.clear_points();
+            }
+        }
+        for imported_fluid in fluids {
+            if let Some(fluid) = physics_data.fluids.get_mut(&imported_fluid.get_rid()) {
+                fluid.load_state(
+                    imported_fluid,
+                    *space_rid,
+                    space_handle,
+                    &fluid_handles,
+                    &mut physics_data.physics_engine,
+                );
+            }
+        }
+    }
+
+    // Replaces the objects of the world of a space with imported ones and relinks the collision
+    // objects to them. Objects that don't exist in this server are dropped. Returns the old to new
+    // joint handles and the number of dropped bodies and colliders.
+    #[cfg(feature = "serde-serialize")]
+    pub(crate) fn import_world(
+        space_handle: WorldHandle,
+        physics_data: &mut PhysicsData,
+        physics_objects: PhysicsObjects,
+    ) -> (HashMap<ImpulseJointHandle, ImpulseJointHandle>, usize) {
+        let collision_objects = &physics_data.collision_objects;
+        let (joint_handles, dropped_objects) =
+            physics_data
+                .physics_engine
+                .world_import(space_handle, physics_objects, |user_data| {
+                    let (rid, _) = RapierCollisionObject::get_collider_user_data(user_data);
+                    collision_objects.contains_key(&rid)
+                });
+        // Old handles are no longer valid, relink using the user data of the new world
+        for collision_object in physics_data.collision_objects.values_mut() {
+            if collision_object.get_base().get_space_handle() == space_handle {
+                collision_object.get_mut_base().reset_handles();
+            }
+        }
+        let mut unlinked_merged_colliders = Vec::new();
+        if let Some(physics_world) = physics_data.physics_engine.get_world(space_handle) {
+            for (body_handle, body) in physics_world.physics_objects.rigid_body_set.iter() {
+                let (rid, _) =
+                    RapierCollisionObject::get_collider_user_data(&UserData::new(body.user_data));
+                if let Some(collision_object) = physics_data.collision_objects.get_mut(&rid) {
+                    collision_object.get_mut_base().set_body_handle(body_handle);
+                }
+            }
+            for (collider_handle, collider) in physics_world.physics_objects.collider_set.iter() {
+                let user_data = UserData::new(collider.user_data);
+                let (rid, _) = RapierCollisionObject::get_collider_user_data(&user_data);
+                if let Some(collision_object) = physics_data.collision_objects.get_mut(&rid) {
+                    if RapierCollisionObject::is_merged_collider(&user_data)
+                        && collision_object.get_base().merged_shapes.is_none()
+                    {
+                        unlinked_merged_colliders.push((rid, collider_handle));
+                        continue;
+                    }
+                    collision_object
+                        .get_mut_base()
+                        .relink_collider(collider_handle, &user_data);
+                }
+            }
+        }
+        // Objects merged only in the imported world get back one collider per shape, and merged
+        // shapes that have no collider in the imported world are dropped
+        for (rid, collider_handle) in unlinked_merged_colliders {
+            physics_data
+                .physics_engine
+                .collider_destroy(space_handle, collider_handle);
+            if let Some(collision_object) = physics_data.collision_objects.get_mut(&rid) {
+                collision_object.recreate_shapes(
+                    &mut physics_data.physics_engine,
+                    &mut physics_data.shapes,
+                    &mut physics_data.spaces,
+                );
+            }
+        }
+        for collision_object in physics_data.collision_objects.values_mut() {
+            if collision_object.get_base().get_space_handle() == space_handle {
+                collision_object
+                    .get_mut_base()
+                    .release_unlinked_merged_shapes(&mut physics_data.physics_engine);
+            }
+        }
+        (joint_handles, dropped_objects)
+    }
+
+    // Deserialized spaces don't own a world, only their state is used
+    #[cfg(feature = "serde-serialize")]
+    pub(crate) fn detach_world(&mut self) {
+        self.handle = WorldHandle::default();
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    pub(crate) fn set_imported_state(&mut self, space: &mut RapierSpace) {
+        self.removed_colliders.clear();
+        self.merged_overlap_shapes.clear();
+        self.active_list = std::mem::take(&mut space.active_list);
+        self.mass_properties_update_list = std::mem::take(&mut space.mass_properties_update_list);
+        self.gravity_update_list = std::mem::take(&mut space.gravity_update_list);
+        self.state_query_list = std::mem::take(&mut space.state_query_list);
+        self.monitor_query_list = std::mem::take(&mut space.monitor_query_list);
+        self.area_update_list = std::mem::take(&mut space.area_update_list);
+        self.body_area_update_list = std::mem::take(&mut space.body_area_update_list);
+        self.contact_max_allowed_penetration = space.contact_max_allowed_penetration;
+        self.default_gravity_dir = space.default_gravity_dir;
+        self.default_gravity_value = space.default_gravity_value;
+        self.default_linear_damping = space.default_linear_damping;
+        self.default_angular_damping = space.default_angular_damping;
+        self.island_count = space.island_count;
+        self.active_objects = space.active_objects;
+        self.collision_pairs = space.collision_pairs;
+        self.time_stepped = space.time_stepped;
+        self.contact_debug_count = 0;
     }
 
     pub fn reset_space_if_empty(&mut self, physics_engine: &mut PhysicsEngine) {
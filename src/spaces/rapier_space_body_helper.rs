@@ -145,6 +145,48 @@ impl RapierSpace {
         collided
     }
 
+    pub fn character_move(
+        &self,
+        body: &RapierBody,
+        desired_translation: Vector,
+        options: &CharacterMoveOptions,
+        physics_engine: &PhysicsEngine,
+        physics_shapes: &PhysicsShapes,
+        physics_collision_objects: &PhysicsCollisionObjects,
+    ) -> CharacterMoveResult {
+        // The character is moved as its first enabled shape
+        let body_base = body.get_base();
+        let Some(shape_idx) = (0..body_base.get_shape_count() as usize)
+            .find(|shape_idx| !body_base.is_shape_disabled(*shape_idx))
+        else {
+            godot_error!("Character body has no enabled shape");
+            return CharacterMoveResult::default();
+        };
+        let Some(shape) = physics_shapes.get(&body_base.get_shape(shape_idx)) else {
+            return CharacterMoveResult::default();
+        };
+        let shape_info = shape_info_from_body_shape(
+            shape.get_handle(),
+            body_base.get_transform() * body_base.get_shape_transform(shape_idx),
+        );
+        let handle_excluded_info = QueryExcludedInfo {
+            query_collision_layer_mask: body_base.get_collision_mask(),
+            query_exclude_body: body_base.get_rid().to_u64() as i64,
+            ..Default::default()
+        };
+        physics_engine.character_move(
+            self.get_handle(),
+            shape_info,
+            vector_to_rapier(desired_translation),
+            options,
+            body.get_solver_groups(),
+            RapierSpace::get_last_step(),
+            &handle_excluded_info,
+            physics_collision_objects,
+            self,
+        )
+    }
+
     pub fn rapier_intersect_aabb(
         &self,
         aabb: Rect,
@@ -242,22 +284,31 @@ impl RapierSpace {
                         if !result.user_data.is_valid() {
                             continue;
                         }
-                        let (shape_col_object, shape_index) =
+                        let (shape_col_object, merged_shape_index) =
                             RapierCollisionObject::get_collider_user_data(&result.user_data);
                         if let Some(shape_col_object) =
                             physics_collision_objects.get(&shape_col_object)
                         {
-                            if let Some(collision_body) = shape_col_object.get_body() {
-                                if let Some(col_shape) = physics_shapes
-                                    .get(&collision_body.get_base().get_shape(shape_index))
+                            if let Some(collision_body) = shape_col_object.get_body()
+                                && p_body.solver_groups_interact(collision_body)
+                            {
+                                // Bodies with a higher priority than what they hit are pushed out less
+                                let mut recover_ratio = BODY_MOTION_RECOVER_RATIO;
+                                if collision_body.get_base().mode.ord() > BodyMode::STATIC.ord()
+                                    && p_body.get_base().get_collision_priority() > 0.0
+                                {
+                                    let priority_ratio =
+                                        collision_body.get_base().get_collision_priority()
+                                            / p_body.get_base().get_collision_priority();
+                                    recover_ratio *= priority_ratio.clamp(0.0, 1.0);
+                                }
+                                if let Some((col_shape_handle, col_shape_transform)) =
+                                    collision_body
+                                        .get_base()
+                                        .get_collider_shape(merged_shape_index, physics_shapes)
                                 {
-                                    let col_shape_transform =
-                                        collision_body.get_base().get_transform()
-                                            * collision_body
-                                                .get_base()
-                                                .get_shape_transform(shape_index);
                                     let col_shape_info = shape_info_from_body_shape(
-                                        col_shape.get_handle(),
+                                        col_shape_handle,
                                         col_shape_transform,
                                     );
                                     let contact = physics_engine.shapes_contact(
@@ -268,6 +319,10 @@ impl RapierSpace {
                                     if !contact.collided {
                                         continue;
                                     }
+                                    let shape_index = collision_body.get_base().get_shape_index_at(
+                                        merged_shape_index,
+                                        vector_to_godot(contact.pixel_point2),
+                                    );
                                     if physics_engine.should_skip_collision_one_dir(
                                         &contact,
                                         body_shape,
@@ -291,9 +346,8 @@ impl RapierSpace {
                                     let depth = n.dot(a + recover_step) - d;
                                     if depth > min_contact_depth + DEFAULT_EPSILON {
                                         // Only recover if there is penetration.
-                                        recover_step -= n
-                                            * (depth - min_contact_depth)
-                                            * BODY_MOTION_RECOVER_RATIO;
+                                        recover_step -=
+                                            n * (depth - min_contact_depth) * recover_ratio;
                                     }
                                 }
                             }
@@ -380,24 +434,25 @@ impl RapierSpace {
                 let mut best_safe = 1.0;
                 let mut best_unsafe = 1.0;
                 let _stuck = false;
-                for result_idx in 0..result_count {
+                'results: for result_idx in 0..result_count {
                     let result_idx = result_idx as usize;
                     let result = &mut results[result_idx];
                     if !result.user_data.is_valid() {
                         continue;
                     }
-                    let (shape_col_object, shape_index) =
+                    let (shape_col_object, merged_shape_index) =
                         RapierCollisionObject::get_collider_user_data(&result.user_data);
                     if let Some(shape_col_object) = physics_collision_objects.get(&shape_col_object)
                     {
-                        if let Some(collision_body) = shape_col_object.get_body() {
-                            if let Some(col_shape) = physics_shapes
-                                .get(&collision_body.get_base().get_shape(shape_index))
+                        if let Some(collision_body) = shape_col_object.get_body()
+                            && p_body.solver_groups_interact(collision_body)
+                        {
+                            if let Some((col_shape_handle, col_shape_transform)) = collision_body
+                                .get_base()
+                                .get_collider_shape(merged_shape_index, physics_shapes)
                             {
-                                let col_shape_transform = collision_body.get_base().get_transform()
-                                    * collision_body.get_base().get_shape_transform(shape_index);
                                 let col_shape_info = shape_info_from_body_shape(
-                                    col_shape.get_handle(),
+                                    col_shape_handle,
                                     col_shape_transform,
                                 );
                                 // stuck logic, check if body collides in place
@@ -409,6 +464,10 @@ impl RapierSpace {
                                     0.0,
                                 );
                                 if step_contact.collided && !step_contact.within_margin {
+                                    let shape_index = collision_body.get_base().get_shape_index_at(
+                                        merged_shape_index,
+                                        vector_to_godot(step_contact.pixel_point2),
+                                    );
                                     if body_shape.allows_one_way_collision()
                                         && collision_body
                                             .get_base()
@@ -424,7 +483,7 @@ impl RapierSpace {
                                     *p_closest_safe = 0.0;
                                     *p_closest_unsafe = 0.0;
                                     *p_best_body_shape = body_shape_idx as i32; //sadly it's the best
-                                    break;
+                                    break 'results;
                                 }
                                 //just do kinematic solving
                                 let mut low = 0.0;
@@ -479,6 +538,10 @@ impl RapierSpace {
                                 if !contact.collided {
                                     continue;
                                 }
+                                let shape_index = collision_body.get_base().get_shape_index_at(
+                                    merged_shape_index,
+                                    vector_to_godot(contact.pixel_point2),
+                                );
                                 if physics_engine.should_skip_collision_one_dir(
                                     &contact,
                                     body_shape,
@@ -582,18 +645,18 @@ impl RapierSpace {
                 if !result.user_data.is_valid() {
                     continue;
                 }
-                let (shape_col_object, shape_index) =
+                let (shape_col_object, merged_shape_index) =
                     RapierCollisionObject::get_collider_user_data(&result.user_data);
                 if let Some(shape_col_object) = physics_collision_objects.get(&shape_col_object) {
-                    if let Some(collision_body) = shape_col_object.get_body() {
-                        let col_shape_rid = collision_body.get_base().get_shape(shape_index);
-                        if let Some(col_shape) = physics_shapes.get(&col_shape_rid) {
-                            let col_shape_transform = collision_body.get_base().get_transform()
-                                * collision_body.get_base().get_shape_transform(shape_index);
-                            let col_shape_info = shape_info_from_body_shape(
-                                col_shape.get_handle(),
-                                col_shape_transform,
-                            );
+                    if let Some(collision_body) = shape_col_object.get_body()
+                        && p_body.solver_groups_interact(collision_body)
+                    {
+                        if let Some((col_shape_handle, col_shape_transform)) = collision_body
+                            .get_base()
+                            .get_collider_shape(merged_shape_index, physics_shapes)
+                        {
+                            let col_shape_info =
+                                shape_info_from_body_shape(col_shape_handle, col_shape_transform);
                             let contact = physics_engine.shapes_contact(
                                 body_shape_info,
                                 col_shape_info,
@@ -602,6 +665,10 @@ impl RapierSpace {
                             if !contact.collided {
                                 continue;
                             }
+                            let shape_index = collision_body.get_base().get_shape_index_at(
+                                merged_shape_index,
+                                vector_to_godot(contact.pixel_point2),
+                            );
                             if physics_engine.should_skip_collision_one_dir(
                                 &contact,
                                 body_shape_obj,
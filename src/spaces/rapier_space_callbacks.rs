@@ -178,6 +178,31 @@ impl RapierSpace {
             } else if let Some(p_object2) = physics_collision_objects.get_mut(&p_object2) {
                 p_collision_object2 = Some(p_object2);
             }
+            // A merged collider overlaps as a whole, so it reports the merged shape closest to the
+            // area shape, and the same shape again when the overlap stops
+            if event_info.is_started {
+                if let (Some(area), Some(object)) = (&p_collision_object1, &p_collision_object2) {
+                    let area_base = area.get_base();
+                    let area_shape_position =
+                        (area_base.get_transform() * area_base.get_shape_transform(shape1)).origin;
+                    let object_shape = object
+                        .get_base()
+                        .get_shape_index_at(shape2, area_shape_position);
+                    if object_shape != shape2 {
+                        self.add_merged_overlap_shape(
+                            collider_handle1,
+                            collider_handle2,
+                            object_shape,
+                        );
+                        shape2 = object_shape;
+                    }
+                }
+            } else if event_info.is_stopped
+                && let Some(object_shape) =
+                    self.take_merged_overlap_shape(collider_handle1, collider_handle2)
+            {
+                shape2 = object_shape;
+            }
             // collision object 1 area
             if let Some(ref mut p_collision_object1) = p_collision_object1 {
                 if let Some(p_area1) = p_collision_object1.get_mut_area() {
@@ -338,6 +363,12 @@ impl RapierSpace {
         if let Some([p_object1, p_object2]) =
             physics_collision_objects.get_many_mut([&p_object1, &p_object2])
         {
+            let shape1 = p_object1
+                .get_base()
+                .get_shape_index_at(shape1, vector_to_godot(pos1));
+            let shape2 = p_object2
+                .get_base()
+                .get_shape_index_at(shape2, vector_to_godot(pos2));
             let depth = real::max(0.0, -contact_info.pixel_distance); // negative distance means penetration
             let normal = contact_info.normal;
             // send just impulse directly along normal
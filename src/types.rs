@@ -17,6 +17,10 @@ pub type Vector = godot::prelude::Vector3;
 #[cfg(feature = "dim2")]
 pub type Vector = godot::prelude::Vector2;
 #[cfg(feature = "dim3")]
+pub type Vectori = godot::prelude::Vector3i;
+#[cfg(feature = "dim2")]
+pub type Vectori = godot::prelude::Vector2i;
+#[cfg(feature = "dim3")]
 pub type Angle = godot::prelude::Vector3;
 #[cfg(feature = "dim2")]
 pub type Angle = real;
@@ -131,3 +135,45 @@ pub fn variant_to_float(variant: &Variant) -> real {
         _ => 0.0,
     }
 }
+#[cfg(feature = "serde-serialize")]
+pub mod serde_engine_enum {
+    use godot::obj::EngineEnum;
+    use serde::Deserialize;
+    pub fn serialize<E: EngineEnum, S: serde::Serializer>(
+        value: &E,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i32(value.ord())
+    }
+    pub fn deserialize<'de, E: EngineEnum, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<E, D::Error> {
+        let ord = i32::deserialize(deserializer)?;
+        E::try_from_ord(ord)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid enum value {}", ord)))
+    }
+}
+// Maps with keys that are not strings can't be json keys, so store them as a list of entries
+#[cfg(feature = "serde-serialize")]
+pub mod serde_map_as_entries {
+    use std::hash::Hash;
+
+    use hashbrown::HashMap;
+    use serde::Deserialize;
+    use serde::Serialize;
+    pub fn serialize<K: Serialize, V: Serialize, S: serde::Serializer>(
+        map: &HashMap<K, V>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(map.iter())
+    }
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+    where
+        K: Deserialize<'de> + Eq + Hash,
+        V: Deserialize<'de>,
+        D: serde::Deserializer<'de>,
+    {
+        let entries = Vec::<(K, V)>::deserialize(deserializer)?;
+        Ok(entries.into_iter().collect())
+    }
+}